@@ -1,9 +1,14 @@
-use crate::{config, ui, wttr};
-use chrono::{DateTime, Local};
-use crossterm::event::{self, Event, KeyCode};
-use ratatui::backend::CrosstermBackend;
-use ratatui::Terminal;
+use crate::{config, metrics, preview, ui, wttr};
+use chrono::{DateTime, Local, Timelike};
+use crossterm::{
+    event::{self, Event, KeyCode},
+    execute,
+    terminal::SetTitle,
+};
+use ratatui::backend::{CrosstermBackend, TestBackend};
+use ratatui::{layout::Rect, style::Color, Terminal};
 use std::{
+    collections::{HashMap, VecDeque},
     io,
     sync::{mpsc, Arc},
     thread,
@@ -14,170 +19,948 @@ use std::{
 pub struct AppData {
     pub country: Arc<config::Country>,
     pub reports: wttr::WeatherReports,
-    pub summaries: Vec<(String, &'static str)>,
+    /// When each region's report was fetched, which differs between regions loaded from the disk cache.
+    pub region_updated: HashMap<String, DateTime<Local>>,
+    pub summaries: Vec<Summary>,
     pub footer_text: (String, &'static str),
     pub left_text: (String, &'static str),
+    /// Condition markers for the next few hourly slots of the left-hand region.
+    pub outlook: Vec<&'static str>,
+    /// How long the fetch took; `None` when loaded from the disk cache.
+    pub fetch_duration: Option<Duration>,
+    /// Regions that could not be fetched this time, with the error for each.
+    pub failures: HashMap<String, String>,
+    /// How each region's pressure moved since the previous refresh, as an arrow.
+    pub pressure_trends: HashMap<String, char>,
+}
+
+/// One region's line in the main page summary panel.
+pub struct Summary {
+    pub region: String,
+    pub text: String,
+    /// The current temperature as reported, for the text-only band word.
+    pub temp_c: Option<String>,
+    pub icon: &'static str,
+    pub color: Color,
+}
+
+/// What the next digit key on the main page applies to, once armed by `i` or `t`.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum DigitAction {
+    Inspect,
+    Pin,
 }
 
 pub enum AppState {
-    Loading,
+    Loading {
+        started: Instant,
+        /// The region currently being fetched, once the fetch thread reports progress.
+        fetching: Option<String>,
+    },
     Loaded {
-        data: AppData,
+        data: Box<AppData>,
         updated_at: DateTime<Local>,
         last_fetch: Instant,
     },
     Error(String),
 }
 
+impl AppState {
+    fn loading() -> Self {
+        AppState::Loading {
+            started: Instant::now(),
+            fetching: None,
+        }
+    }
+
+    /// Whether this is a fetch that has been loading for longer than `deadline`.
+    fn loading_timed_out(&self, deadline: Duration) -> bool {
+        matches!(self, AppState::Loading { started, .. } if started.elapsed() > deadline)
+    }
+}
+
+/// Messages sent from the fetch thread back to the UI loop.
+pub enum FetchMessage {
+    Progress { region: String, city: String },
+    Finished(Result<Box<AppData>, String>),
+}
+
+/// Previously-fetched data for a country, retained across country switches.
+pub struct CachedCountry {
+    pub data: Box<AppData>,
+    pub updated_at: DateTime<Local>,
+    pub last_fetch: Instant,
+}
+
+pub type CountryCache = HashMap<String, CachedCountry>;
+
 // ViewState now includes scroll position for list-based views.
 pub enum ViewState {
     Main,
     Details { scroll: u16 },
-    Hourly { region_index: usize, scroll: u16 },
-    SelectCountry { available: Vec<String>, scroll: u16 },
+    Forecast { scroll: u16 },
+    /// `goto` holds the time being typed after `g`, if any.
+    Hourly { region_index: usize, day: usize, scroll: u16, goto: Option<String> },
+    /// `selected` is the highlighted row, which Enter chooses.
+    SelectCountry { available: Vec<String>, selected: usize },
+    BigIcon,
+    ChangeLog { scroll: u16 },
+    Ranking { scroll: u16 },
+}
+
+/// Opens `next`, remembering the current view so Esc can return to it.
+fn open_view(current: &mut ViewState, history: &mut Vec<ViewState>, next: ViewState) {
+    history.push(std::mem::replace(current, next));
+}
+
+/// Returns to the view the current one was opened from, never going further back than Main.
+fn go_back(current: &mut ViewState, history: &mut Vec<ViewState>) {
+    *current = history.pop().unwrap_or(ViewState::Main);
+}
+
+/// Returns straight to Main, forgetting the views in between.
+fn go_home(current: &mut ViewState, history: &mut Vec<ViewState>) {
+    history.clear();
+    *current = ViewState::Main;
+}
+
+/// Time left before the next auto-refresh, or `None` while auto-refresh is paused. The interval
+/// runs from the last fetch or the last resume, whichever is more recent, so resuming never
+/// fetches straight away. Zero means a refresh is due.
+fn refresh_remaining(since_fetch: Duration, since_resume: Option<Duration>, refresh: Duration, paused: bool) -> Option<Duration> {
+    if paused {
+        return None;
+    }
+    let since = since_resume.map_or(since_fetch, |since_resume| since_resume.min(since_fetch));
+    Some(refresh.saturating_sub(since))
+}
+
+/// Waits until a fetch that finished at `fetched` is due again, a full `--refresh` after a
+/// success but sooner after a failure. Returns `false` instead once `--exit-after` has elapsed.
+fn wait_for_refresh(cli: &config::Cli, fetched: Instant, failed: bool) -> bool {
+    let interval = if failed { cli.refresh.min(config::ERROR_RETRY_INTERVAL) } else { cli.refresh };
+    loop {
+        if cli.exit_due() {
+            return false;
+        }
+        match refresh_remaining(fetched.elapsed(), None, interval, false) {
+            Some(left) if !left.is_zero() => thread::sleep(left.min(Duration::from_millis(250))),
+            _ => return true,
+        }
+    }
+}
+
+/// A key that moves through a list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ListKey {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+}
+
+impl ListKey {
+    /// Arrows and Vim's `j`/`k` move one row, PageUp/PageDown a screenful, Home/End to either end.
+    fn from_code(code: KeyCode) -> Option<ListKey> {
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => Some(ListKey::Up),
+            KeyCode::Down | KeyCode::Char('j') => Some(ListKey::Down),
+            KeyCode::PageUp => Some(ListKey::PageUp),
+            KeyCode::PageDown => Some(ListKey::PageDown),
+            KeyCode::Home => Some(ListKey::Home),
+            KeyCode::End => Some(ListKey::End),
+            _ => None,
+        }
+    }
+
+    /// The position after this key, kept between 0 and `last`; paging moves by `page` rows.
+    fn apply(self, position: usize, last: usize, page: usize) -> usize {
+        let page = page.max(1);
+        match self {
+            ListKey::Up => position.saturating_sub(1),
+            ListKey::Down => position.saturating_add(1),
+            ListKey::PageUp => position.saturating_sub(page),
+            ListKey::PageDown => position.saturating_add(page),
+            ListKey::Home => 0,
+            ListKey::End => last,
+        }
+        .min(last)
+    }
+}
+
+impl ViewState {
+    /// Moves a list view for a `ListKey`, returning whether the key was one. Scrolling stops once
+    /// the last row is on screen, using the list's `extent` as last drawn. Views without a list,
+    /// and the hourly page while a time is typed, ignore these keys.
+    fn scroll_by_key(&mut self, code: KeyCode, extent: ui::ListExtent) -> bool {
+        let Some(key) = ListKey::from_code(code) else {
+            return false;
+        };
+        let page = extent.visible as usize;
+        match self {
+            ViewState::Details { scroll }
+            | ViewState::Forecast { scroll }
+            | ViewState::ChangeLog { scroll }
+            | ViewState::Ranking { scroll }
+            | ViewState::Hourly { scroll, goto: None, .. } => {
+                let last = extent.max_scroll().min(u16::MAX as usize);
+                *scroll = key.apply(*scroll as usize, last, page) as u16;
+            }
+            ViewState::SelectCountry { available, selected } => {
+                *selected = key.apply(*selected, available.len().saturating_sub(1), page);
+            }
+            _ => return false,
+        }
+        true
+    }
 }
 
 fn spawn_fetch_thread(
-    tx: mpsc::Sender<Result<AppData, String>>,
+    tx: mpsc::Sender<FetchMessage>,
     country: Arc<config::Country>,
     client: Arc<dyn wttr::WeatherClient>,
+    options: config::DisplayOptions,
+    fresh: bool,
 ) {
+    // A refresh the user asked for goes to the network; the cache only serves startup and timed refreshes.
+    let client: Arc<dyn wttr::WeatherClient> = if fresh { Arc::new(wttr::Uncached(client)) } else { client };
     thread::spawn(move || {
-        let mut weather_reports = std::collections::HashMap::new();
-        let mut summaries = Vec::new();
-        for region in country.regions.iter() {
-            match client.fetch(&region.city) {
-                Ok(report) => {
-                    if let Some(condition) = report.current_condition.first() {
-                        let desc = condition.weatherDesc.first().map_or("N/A", |d| &d.value);
-                        let icon = wttr::get_weather_icon(desc);
-                        summaries.push((format!("{}: {}", region.name, desc), icon));
-                        weather_reports.insert(region.name.clone(), report.clone());
+        let result = fetch_country_data(country, client.as_ref(), &options, |region| {
+            let _ = tx.send(FetchMessage::Progress {
+                region: region.name.clone(),
+                city: region.city.clone(),
+            });
+        });
+        let _ = tx.send(FetchMessage::Finished(result.map(Box::new)));
+    });
+}
+
+/// Fetches every region of a country at once and builds the page data from the reports,
+/// calling `progress` with the first region still outstanding whenever that changes.
+/// Regions that fail are recorded in `failures`; only if every region fails is the first error returned.
+fn fetch_country_data(
+    country: Arc<config::Country>,
+    client: &dyn wttr::WeatherClient,
+    options: &config::DisplayOptions,
+    mut progress: impl FnMut(&config::Region),
+) -> Result<AppData, String> {
+    let started = Instant::now();
+    let cities: Vec<&str> = country.regions.iter().map(|region| region.city.as_str()).collect();
+    let mut batch = client.fetch_many(&cities).map(Vec::into_iter);
+    let mut results: Vec<Option<Result<Option<wttr::WeatherReport>, String>>> = country.regions.iter().map(|_| None).collect();
+    thread::scope(|scope| {
+        let (tx, rx) = mpsc::channel();
+        for (index, region) in country.regions.iter().enumerate() {
+            let batched = batch.as_mut().and_then(Iterator::next);
+            let tx = tx.clone();
+            scope.spawn(move || {
+                let _ = tx.send((index, fetch_region(client, region, batched)));
+            });
+        }
+        drop(tx);
+
+        let mut waiting_on = 0;
+        if let Some(region) = country.regions.first() {
+            progress(region);
+        }
+        for (index, result) in rx {
+            results[index] = Some(result);
+            let outstanding = results.iter().position(Option::is_none);
+            if let Some(next) = outstanding.filter(|&next| next != waiting_on) {
+                waiting_on = next;
+                progress(&country.regions[next]);
+            }
+        }
+    });
+
+    let mut weather_reports = HashMap::new();
+    let mut region_updated = HashMap::new();
+    let mut failures = HashMap::new();
+    let mut first_error = None;
+    for (region, result) in country.regions.iter().zip(results) {
+        match result.unwrap_or_else(|| Err(format!("Fetching {} stopped unexpectedly.", region.city))) {
+            Ok(Some(report)) => {
+                weather_reports.insert(region.name.clone(), report);
+                region_updated.insert(region.name.clone(), Local::now());
+            }
+            Ok(None) => {}
+            Err(e) => {
+                first_error.get_or_insert_with(|| e.clone());
+                failures.insert(region.name.clone(), e);
+            }
+        }
+    }
+    if let Some(e) = first_error.filter(|_| failures.len() == country.regions.len()) {
+        return Err(e);
+    }
+
+    let mut data = build_app_data(country, weather_reports, region_updated, options);
+    data.fetch_duration = Some(started.elapsed());
+    data.failures = failures;
+    Ok(data)
+}
+
+/// Fetches one region, starting from its main city's result in a `fetch_many` batch if there was one.
+fn fetch_region(
+    client: &dyn wttr::WeatherClient,
+    region: &config::Region,
+    batched: Option<Result<wttr::WeatherReport, String>>,
+) -> Result<Option<wttr::WeatherReport>, String> {
+    let queries = region.queries();
+    let result = match batched {
+        Some(Ok(report)) if !report.current_condition.is_empty() => Ok(Some(report)),
+        // The city was already tried as part of the batch, so move on to the fallbacks.
+        Some(first) if queries.len() == 1 => first.map(|_| None),
+        Some(_) => wttr::fetch_first_valid(client, &queries[1..]),
+        None => wttr::fetch_first_valid(client, &queries),
+    };
+    Ok(result?.and_then(|report| with_extra_cities(client, region, report)))
+}
+
+/// Fetches a region's extra cities and combines them with its main report.
+/// An extra city that fails to answer is left out rather than failing the whole fetch.
+fn with_extra_cities(client: &dyn wttr::WeatherClient, region: &config::Region, report: wttr::WeatherReport) -> Option<wttr::WeatherReport> {
+    if region.cities.is_empty() {
+        return Some(report);
+    }
+    let extra = region.cities.iter().filter_map(|city| client.fetch(city).ok());
+    wttr::aggregate_reports(std::iter::once(report).chain(extra).collect(), region.aggregate)
+}
+
+/// Derives the summary panel, footer and left-hand text from a set of reports.
+fn build_app_data(
+    country: Arc<config::Country>,
+    weather_reports: wttr::WeatherReports,
+    region_updated: HashMap<String, DateTime<Local>>,
+    options: &config::DisplayOptions,
+) -> AppData {
+    let mut summaries = Vec::new();
+    for region in country.regions.iter() {
+        let condition = weather_reports
+            .get(&region.name)
+            .and_then(|report| report.current_condition.first());
+        if let Some(condition) = condition {
+            let desc = condition.weatherDesc.first().map_or("N/A", |d| &d.value);
+            let icon = wttr::condition_marker(wttr::icon_description(&condition.weatherCode, desc), options.text_only, options.icons);
+            summaries.push(Summary {
+                region: region.name.clone(),
+                text: format!("{}: {}", region.name, ui::truncate_to_width(desc, config::MAX_DESC_WIDTH)),
+                temp_c: Some(condition.temp_C.clone()),
+                icon,
+                color: wttr::get_condition_color(desc),
+            });
+        }
+    }
+
+    let footer_condition = country.footer_region()
+        .and_then(|region| weather_reports.get(&region.name))
+        .and_then(|report| report.current_condition.first());
+    let footer_desc = footer_condition
+        .and_then(|condition| condition.weatherDesc.first())
+        .map_or_else(|| "Weather summary unavailable.".to_string(), |desc| desc.value.clone());
+    let footer_code = footer_condition.map_or("", |condition| condition.weatherCode.as_str());
+    let footer_icon = wttr::condition_marker(wttr::icon_description(footer_code, &footer_desc), options.text_only, options.icons);
+    let footer_text = (ui::truncate_to_width(&footer_desc, config::MAX_DESC_WIDTH), footer_icon);
+
+    let left_report = country.left_region()
+        .and_then(|region| weather_reports.get(&region.name));
+    let outlook = left_report
+        .map(|report| {
+            wttr::upcoming_hourly(report, Local::now().hour(), config::OUTLOOK_SLOTS)
+                .into_iter()
+                .map(|hourly| {
+                    let desc = hourly.weatherDesc.first().map_or("", |d| d.value.as_str());
+                    wttr::condition_marker(wttr::icon_description(&hourly.weatherCode, desc), options.text_only, options.icons)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let left_condition = left_report.and_then(|report| report.current_condition.first());
+    let left_desc = left_condition
+        .and_then(|condition| condition.weatherDesc.first())
+        .map_or_else(|| "No specific forecast.".to_string(), |desc| desc.value.clone());
+    let left_code = left_condition.map_or("", |condition| condition.weatherCode.as_str());
+    let left_icon = wttr::condition_marker(wttr::icon_description(left_code, &left_desc), options.text_only, options.icons);
+    let left_text = (left_desc, left_icon);
+
+    AppData {
+        country,
+        reports: weather_reports,
+        region_updated,
+        summaries,
+        footer_text,
+        left_text,
+        outlook,
+        fetch_duration: None,
+        failures: HashMap::new(),
+        pressure_trends: HashMap::new(),
+    }
+}
+
+/// Builds `AppData` straight from the client's fresh cache entries, if every region has one.
+/// Returns the data together with the age of its oldest report.
+fn load_cached_app_data(
+    country: &Arc<config::Country>,
+    client: &dyn wttr::WeatherClient,
+    options: &config::DisplayOptions,
+) -> Option<(AppData, Duration)> {
+    let mut weather_reports = HashMap::new();
+    let mut region_updated = HashMap::new();
+    let mut oldest = Duration::ZERO;
+    for region in country.regions.iter() {
+        let (report, age) = region.queries().into_iter().find_map(|query| client.cached(query))?;
+        oldest = oldest.max(age);
+        if !report.current_condition.is_empty() {
+            weather_reports.insert(region.name.clone(), report);
+            region_updated.insert(region.name.clone(), Local::now() - chrono::Duration::from_std(age).unwrap_or_default());
+        }
+    }
+    Some((build_app_data(country.clone(), weather_reports, region_updated, options), oldest))
+}
+
+/// Each reported region's condition description, keyed by region name.
+fn conditions_by_region(data: &AppData) -> HashMap<String, String> {
+    data.reports
+        .iter()
+        .filter_map(|(name, report)| {
+            let desc = report.current_condition.first()?.weatherDesc.first()?;
+            Some((name.clone(), desc.value.clone()))
+        })
+        .collect()
+}
+
+/// Each reported region's pressure in millibars, keyed by region name.
+fn pressures_by_region(data: &AppData) -> HashMap<String, i32> {
+    data.reports
+        .iter()
+        .filter_map(|(name, report)| {
+            let pressure = report.current_condition.first()?.pressure.trim().parse().ok()?;
+            Some((name.clone(), pressure))
+        })
+        .collect()
+}
+
+/// Describes each region whose condition differs from the previous refresh, e.g. "London: Sunny → Rain".
+fn condition_changes(
+    previous: &HashMap<String, String>,
+    current: &HashMap<String, String>,
+    regions: &[config::Region],
+) -> Vec<String> {
+    regions
+        .iter()
+        .filter_map(|region| {
+            let (before, after) = (previous.get(&region.name)?, current.get(&region.name)?);
+            (before != after).then(|| format!("{}: {} → {}", region.city, before, after))
+        })
+        .collect()
+}
+
+/// Index of the region with the highest (or, if not `warmest`, lowest) current temperature.
+fn extreme_region_index(data: &AppData, warmest: bool) -> Option<usize> {
+    let temps = data.country.regions.iter().enumerate().filter_map(|(index, region)| {
+        let condition = data.reports.get(&region.name)?.current_condition.first()?;
+        Some((index, wttr::parse_temp_rounded(&condition.temp_C)?))
+    });
+    let extreme = if warmest {
+        temps.max_by_key(|&(_, temp)| temp)
+    } else {
+        temps.min_by_key(|&(_, temp)| temp)
+    };
+    extreme.map(|(index, _)| index)
+}
+
+/// The terminal title for the featured region, e.g. "London 22°C Sunny — ceefax-weather".
+fn window_title(data: &AppData, options: &config::DisplayOptions) -> String {
+    let condition = data.country.footer_region().and_then(|region| {
+        let condition = data.reports.get(&region.name)?.current_condition.first()?;
+        let desc = condition.weatherDesc.first().map_or("", |desc| desc.value.as_str());
+        Some(format!("{} {} {}", region.city, wttr::format_temp(&condition.temp_C, options.units), desc))
+    });
+    match condition {
+        Some(condition) => format!("{} — ceefax-weather", condition.trim_end()),
+        None => "ceefax-weather".to_string(),
+    }
+}
+
+/// Shows the color calibration page until any key other than `b`, which cycles the band schemes, is pressed.
+pub fn run_colortest(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<()> {
+    let mut scheme = wttr::TempScheme::default();
+    loop {
+        terminal.draw(|f| {
+            ui::clear_page(f);
+            ui::colortest_ui(f, scheme);
+        })?;
+        match event::read()? {
+            Event::Key(key) if key.code == KeyCode::Char('b') => scheme = scheme.next(),
+            Event::Key(_) => return Ok(()),
+            _ => {}
+        }
+    }
+}
+
+/// Size of the off-screen frame rendered for `--serve`.
+const PREVIEW_SIZE: (u16, u16) = (100, 30);
+
+/// Runs the fetch/refresh loop without a terminal, publishing each rendered main page to `preview`.
+pub fn run_headless(
+    country_name: &str,
+    country: config::Country,
+    client: Arc<dyn wttr::WeatherClient>,
+    cli: &config::Cli,
+    metrics: &metrics::Metrics,
+    preview: &preview::Preview,
+) -> io::Result<()> {
+    let options = config::DisplayOptions::from(cli);
+    let country_arc = Arc::new(country);
+    let mut terminal = Terminal::new(TestBackend::new(PREVIEW_SIZE.0, PREVIEW_SIZE.1))?;
+    let main_page = ui::MainPageState {
+        show_attribution: cli.attribution,
+        show_fetch_time: cli.show_fetch_time,
+        provider: client.provider_name(),
+        pinned_region: cli.pin_region.clone(),
+        ..Default::default()
+    };
+
+    loop {
+        if cli.exit_due() {
+            return Ok(());
+        }
+        let (tx, rx) = mpsc::channel();
+        let started = Instant::now();
+        let mut failed = true;
+        spawn_fetch_thread(tx, country_arc.clone(), client.clone(), options.clone(), false);
+        for message in rx {
+            let frame = terminal.draw(|f| {
+                ui::clear_page(f);
+                match &message {
+                    FetchMessage::Progress { region, city } => {
+                        let fetching = format!("{} ({})", region, city);
+                        ui::loading_ui(f, &options, 100, started.elapsed(), Some(&fetching));
                     }
+                    FetchMessage::Finished(Ok(data)) => ui::main_ui(f, data, &options, &Local::now(), &main_page),
+                    FetchMessage::Finished(Err(e)) => ui::error_ui(f, e),
                 }
-                Err(e) => {
-                    let _ = tx.send(Err(e));
-                    return;
+                ui::apply_theme(f, &options.theme);
+                ui::apply_color_mode(f, options.color_mode);
+            })?;
+            preview.update(frame.buffer);
+            match message {
+                FetchMessage::Finished(Ok(data)) => {
+                    metrics.record_success(country_name, &data);
+                    failed = false;
                 }
+                FetchMessage::Finished(Err(_)) => metrics.record_failure(),
+                FetchMessage::Progress { .. } => {}
             }
         }
+        if !wait_for_refresh(cli, Instant::now(), failed) {
+            return Ok(());
+        }
+    }
+}
 
-        let footer_desc = country.regions.first()
-            .and_then(|region| weather_reports.get(&region.name))
-            .and_then(|report| report.current_condition.first())
-            .and_then(|condition| condition.weatherDesc.first())
-            .map_or_else(|| "Weather summary unavailable.".to_string(), |desc| desc.value.clone());
-        let footer_icon = wttr::get_weather_icon(&footer_desc);
-        let footer_text = (footer_desc, footer_icon);
-
-        let left_desc = country.regions.get(1)
-            .or_else(|| country.regions.first())
-            .and_then(|region| weather_reports.get(&region.name))
-            .and_then(|report| report.current_condition.first())
-            .and_then(|condition| condition.weatherDesc.first())
-            .map_or_else(|| "No specific forecast.".to_string(), |desc| desc.value.clone());
-        let left_icon = wttr::get_weather_icon(&left_desc);
-        let left_text = (left_desc, left_icon);
-
-        let _ = tx.send(Ok(AppData {
-            country,
-            reports: weather_reports,
-            summaries,
-            footer_text,
-            left_text,
-        }));
-    });
+/// Runs the fetch/refresh loop without a terminal, writing one JSON line per refresh to `out`,
+/// until `--exit-after` elapses.
+pub fn run_stream(
+    country_name: &str,
+    country: config::Country,
+    client: Arc<dyn wttr::WeatherClient>,
+    cli: &config::Cli,
+    metrics: &metrics::Metrics,
+    out: &mut impl io::Write,
+) -> io::Result<()> {
+    let options = config::DisplayOptions::from(cli);
+    let country_arc = Arc::new(country);
+
+    loop {
+        let (tx, rx) = mpsc::channel();
+        spawn_fetch_thread(tx, country_arc.clone(), client.clone(), options.clone(), false);
+        let result = rx.into_iter().find_map(|message| match message {
+            FetchMessage::Finished(result) => Some(result),
+            FetchMessage::Progress { .. } => None,
+        });
+        let timestamp = Local::now().to_rfc3339();
+        let failed = !matches!(result, Some(Ok(_)));
+        let line = match result {
+            Some(Ok(data)) => {
+                metrics.record_success(country_name, &data);
+                let regions: Vec<serde_json::Value> = data.country.regions.iter()
+                    .filter_map(|region| {
+                        let condition = data.reports.get(&region.name)?.current_condition.first()?;
+                        Some(serde_json::json!({
+                            "region": region.name,
+                            "city": region.city,
+                            "temp_c": wttr::parse_temp_rounded(&condition.temp_C),
+                            "feels_like_c": wttr::parse_temp_rounded(&condition.FeelsLikeC),
+                            "condition": condition.weatherDesc.first().map(|desc| desc.value.as_str()),
+                            "wind_kmph": condition.windspeedKmph.parse::<i32>().ok(),
+                            "wind_dir": condition.winddir16Point,
+                            "precip_mm": condition.precipMM.parse::<f64>().ok(),
+                        }))
+                    })
+                    .collect();
+                serde_json::json!({ "timestamp": timestamp, "country": country_name, "regions": regions })
+            }
+            Some(Err(e)) => {
+                metrics.record_failure();
+                serde_json::json!({ "timestamp": timestamp, "country": country_name, "error": e })
+            }
+            None => serde_json::json!({ "timestamp": timestamp, "country": country_name, "error": "fetch thread stopped" }),
+        };
+        writeln!(out, "{}", line)?;
+        out.flush()?;
+        if !wait_for_refresh(cli, Instant::now(), failed) {
+            return Ok(());
+        }
+    }
 }
 
 pub fn run_app(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    country_name: &str,
     country: config::Country,
     client: Arc<dyn wttr::WeatherClient>,
+    cli: &config::Cli,
+    cache: &mut CountryCache,
+    metrics: &metrics::Metrics,
 ) -> io::Result<Option<String>> {
+    let mut options = config::DisplayOptions::from(cli);
     let country_arc = Arc::new(country);
     let (tx, rx) = mpsc::channel();
-    spawn_fetch_thread(tx.clone(), country_arc.clone(), client.clone());
 
-    let mut app_state = AppState::Loading;
-    let mut view_state = ViewState::Main;
+    // Reuse retained data for this country if it is still within the refresh interval.
+    let cached = cache
+        .remove(country_name)
+        .filter(|cached| cli.keep_countries_cached && cached.last_fetch.elapsed() <= cli.refresh);
+    // Otherwise start straight from the disk cache when it is still fresh, unless told not to.
+    let from_disk = match cached {
+        None if !cli.force_refresh => load_cached_app_data(&country_arc, client.as_ref(), &options),
+        _ => None,
+    };
+    let mut app_state = match (cached, from_disk) {
+        (Some(cached), _) => AppState::Loaded {
+            data: cached.data,
+            updated_at: cached.updated_at,
+            last_fetch: cached.last_fetch,
+        },
+        (None, Some((data, age))) => {
+            // The cached page stays up while the new reports arrive.
+            if cli.background_refresh {
+                spawn_fetch_thread(tx.clone(), country_arc.clone(), client.clone(), options.clone(), true);
+            }
+            AppState::Loaded {
+                data: Box::new(data),
+                updated_at: Local::now() - chrono::Duration::from_std(age).unwrap_or_default(),
+                last_fetch: Instant::now().checked_sub(age).unwrap_or_else(Instant::now),
+            }
+        }
+        (None, None) => {
+            spawn_fetch_thread(tx.clone(), country_arc.clone(), client.clone(), options.clone(), false);
+            AppState::loading()
+        }
+    };
+    let mut view_state = match cli.start_view {
+        config::StartView::Main => ViewState::Main,
+        config::StartView::Details => ViewState::Details { scroll: 0 },
+        config::StartView::Forecast => ViewState::Forecast { scroll: 0 },
+        config::StartView::Picture => ViewState::BigIcon,
+        config::StartView::Ranking => ViewState::Ranking { scroll: 0 },
+    };
+    // Views to return to with Esc, most recent last; Main is always the bottom.
+    let mut history: Vec<ViewState> = Vec::new();
     let mut counter: u16 = 100;
+    let mut main_page = ui::MainPageState {
+        show_attribution: cli.attribution,
+        show_fetch_time: cli.show_fetch_time,
+        provider: client.provider_name(),
+        ..Default::default()
+    };
+    let mut notice_shown_at = Instant::now();
+    // When auto-refresh was last resumed with 'p', which restarts its interval.
+    let mut resumed_at: Option<Instant> = None;
+    let mut next_country: Option<String> = None;
+    // Notable condition changes across refreshes, newest first, for the P188 page.
+    let mut change_log: VecDeque<String> = VecDeque::new();
+    let mut previous_conditions = match &app_state {
+        AppState::Loaded { data, .. } => conditions_by_region(data),
+        _ => HashMap::new(),
+    };
+    let mut previous_pressures = match &app_state {
+        AppState::Loaded { data, .. } => pressures_by_region(data),
+        _ => HashMap::new(),
+    };
+    // The data the terminal title was last set from.
+    let mut titled_at: Option<DateTime<Local>> = None;
+    // Set by `i` or `t`; the next digit explains or pins that region.
+    let mut digit_armed: Option<DigitAction> = None;
+    // When the current page started painting in, while `--transitions` is revealing it.
+    let mut reveal_started: Option<Instant> = None;
+    // The current page's list as last drawn, for paging and clamping its scroll.
+    let mut list_extent = ui::ListExtent::default();
 
     loop {
-        terminal.draw(|f| match &app_state {
-            AppState::Loading => ui::loading_ui(f, counter),
-            AppState::Loaded {
-                data, updated_at, ..
-            } => match &view_state {
-                ViewState::Main => ui::main_ui(f, data, updated_at),
-                ViewState::Details { scroll } => ui::details_ui(f, data, *scroll),
-                ViewState::Hourly { region_index, scroll } => ui::hourly_ui(f, data, *region_index, *scroll),
-                ViewState::SelectCountry { available, scroll } => ui::select_country_ui(f, available, *scroll),
-            },
-            AppState::Error(e) => ui::error_ui(f, e),
+        if cli.exit_due() {
+            return Ok(None);
+        }
+        if let AppState::Loaded { data, updated_at, .. } = &app_state {
+            if !cli.no_title_update && titled_at != Some(*updated_at) {
+                execute!(terminal.backend_mut(), SetTitle(window_title(data, &options)))?;
+                titled_at = Some(*updated_at);
+            }
+        }
+        if let AppState::Loaded { last_fetch, .. } = &app_state {
+            main_page.stale = last_fetch.elapsed() > cli.stale_threshold();
+            main_page.next_refresh = refresh_remaining(
+                last_fetch.elapsed(),
+                resumed_at.map(|resumed| resumed.elapsed()),
+                cli.refresh,
+                main_page.paused,
+            );
+        }
+
+        terminal.draw(|f| {
+            ui::clear_page(f);
+            match &app_state {
+                AppState::Loading { started, fetching } => ui::loading_ui(f, &options, counter, started.elapsed(), fetching.as_deref()),
+                AppState::Loaded {
+                    data, updated_at, ..
+                } => match &view_state {
+                    ViewState::Main => ui::main_ui(f, data, &options, updated_at, &main_page),
+                    ViewState::Details { scroll } => list_extent = ui::details_ui(f, data, &options, *scroll),
+                    ViewState::Forecast { scroll } => list_extent = ui::forecast_ui(f, data, &options, *scroll),
+                    ViewState::Hourly { region_index, day, scroll, goto } => {
+                        list_extent = ui::hourly_ui(f, data, &options, *region_index, *day, *scroll, goto.as_deref())
+                    }
+                    ViewState::SelectCountry { available, selected } => list_extent = ui::select_country_ui(f, available, *selected),
+                    ViewState::BigIcon => ui::big_icon_ui(f, data, &options),
+                    ViewState::ChangeLog { scroll } => list_extent = ui::change_log_ui(f, &change_log, *scroll),
+                    ViewState::Ranking { scroll } => list_extent = ui::ranking_ui(f, data, &options, *scroll),
+                },
+                AppState::Error(e) => ui::error_ui(f, e),
+            }
+            if let Some(started) = reveal_started {
+                ui::apply_reveal(f, started.elapsed());
+            }
+            ui::apply_theme(f, &options.theme);
+            ui::apply_color_mode(f, options.color_mode);
         })?;
+        if reveal_started.is_some_and(|started| started.elapsed() >= config::REVEAL_DURATION) {
+            reveal_started = None;
+        }
 
-        if event::poll(Duration::from_millis(50))? {
-            if let Event::Key(key) = event::read()? {
+        // Poll quickly while a page is painting in so the reveal animates smoothly.
+        let poll_timeout = if reveal_started.is_some() { 15 } else { 50 };
+        let shown_view = std::mem::discriminant(&view_state);
+        if event::poll(Duration::from_millis(poll_timeout))? {
+            let event = event::read()?;
+            if let Event::Resize(width, height) = event {
+                // Resize the buffers (which clears once) and redraw straight away.
+                terminal.resize(Rect::new(0, 0, width, height))?;
+                continue;
+            }
+            if let Event::Key(key) = event {
+                let armed = digit_armed.take();
                 match &mut app_state {
                     AppState::Error(_) => match key.code {
                         KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
                         KeyCode::Char('r') => {
-                            app_state = AppState::Loading;
-                            spawn_fetch_thread(tx.clone(), country_arc.clone(), client.clone());
+                            app_state = AppState::loading();
+                            spawn_fetch_thread(tx.clone(), country_arc.clone(), client.clone(), options.clone(), true);
                         }
                         _ => {}
                     },
+                    // List movement takes precedence over each view's own keys.
+                    AppState::Loaded { .. } if view_state.scroll_by_key(key.code, list_extent) => {}
                     AppState::Loaded { data, .. } => match &mut view_state {
                         ViewState::Main => match key.code {
+                            KeyCode::Char(c) if c.is_ascii_digit() && armed == Some(DigitAction::Inspect) => {
+                                let index = c.to_digit(10).unwrap_or(0) as usize;
+                                if let Some(region) = index.checked_sub(1).and_then(|i| data.country.regions.get(i)) {
+                                    let temp = data.reports.get(&region.name)
+                                        .and_then(|report| report.current_condition.first())
+                                        .and_then(|condition| wttr::parse_temp_rounded(&condition.temp_C));
+                                    let explanation = match temp {
+                                        Some(temp) => {
+                                            let scale = options.units.scale();
+                                            wttr::explain_temp_color(scale.convert(temp), scale, options.temp_scheme)
+                                        }
+                                        None => "no temperature reading, so drawn grey".to_string(),
+                                    };
+                                    main_page.notice = Some(format!("{}: {}", region.name, explanation));
+                                    notice_shown_at = Instant::now();
+                                }
+                            }
+                            KeyCode::Char(c) if c.is_ascii_digit() && armed == Some(DigitAction::Pin) => {
+                                let index = c.to_digit(10).unwrap_or(0) as usize;
+                                if let Some(region) = index.checked_sub(1).and_then(|i| data.country.regions.get(i)) {
+                                    if main_page.pinned_region.as_deref() == Some(region.name.as_str()) {
+                                        main_page.pinned_region = None;
+                                        main_page.notice = Some(format!("{} unpinned", region.name));
+                                    } else {
+                                        main_page.pinned_region = Some(region.name.clone());
+                                        main_page.notice = Some(format!("{} pinned to the top", region.name));
+                                    }
+                                    notice_shown_at = Instant::now();
+                                }
+                            }
+                            KeyCode::Char(c @ ('w' | 'x')) => match extreme_region_index(data, c == 'w') {
+                                Some(region_index) => {
+                                    open_view(&mut view_state, &mut history, ViewState::Hourly { region_index, day: 0, scroll: 0, goto: None })
+                                }
+                                None => {
+                                    main_page.notice = Some("No temperatures to compare yet".to_string());
+                                    notice_shown_at = Instant::now();
+                                }
+                            },
+                            KeyCode::Char('t') => {
+                                digit_armed = Some(DigitAction::Pin);
+                                main_page.notice = Some(format!(
+                                    "Pin to top: press a region number (1-{}), or the pinned one to unpin",
+                                    data.country.regions.len().min(config::DIGIT_KEYS)
+                                ));
+                                notice_shown_at = Instant::now();
+                            }
+                            KeyCode::Char('i') => {
+                                digit_armed = Some(DigitAction::Inspect);
+                                main_page.notice = Some(format!(
+                                    "Explain colour: press a region number (1-{})",
+                                    data.country.regions.len().min(config::DIGIT_KEYS)
+                                ));
+                                notice_shown_at = Instant::now();
+                            }
                             KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
-                            KeyCode::Char('d') => view_state = ViewState::Details { scroll: 0 },
+                            KeyCode::Char('d') => open_view(&mut view_state, &mut history, ViewState::Details { scroll: 0 }),
+                            KeyCode::Char('f') => open_view(&mut view_state, &mut history, ViewState::Forecast { scroll: 0 }),
+                            KeyCode::Char('g') => open_view(&mut view_state, &mut history, ViewState::BigIcon),
+                            KeyCode::Char('h') => open_view(&mut view_state, &mut history, ViewState::ChangeLog { scroll: 0 }),
+                            KeyCode::Char('n') => open_view(&mut view_state, &mut history, ViewState::Ranking { scroll: 0 }),
+                            KeyCode::Char('a') => main_page.show_attribution = !main_page.show_attribution,
+                            KeyCode::Char('v') => main_page.show_wind = !main_page.show_wind,
+                            KeyCode::Char('b') => options.temp_scheme = options.temp_scheme.next(),
+                            KeyCode::Char('l') => main_page.show_legend = !main_page.show_legend,
+                            KeyCode::Char('s') => {
+                                if let Some(region) = data.country.footer_region() {
+                                    main_page.notice = Some(format!("Share: {}", wttr::location_url(&region.city)));
+                                    notice_shown_at = Instant::now();
+                                }
+                            }
                             KeyCode::Char('c') => {
                                 if let Ok(available) = config::get_available_countries() {
-                                    view_state = ViewState::SelectCountry { available, scroll: 0 };
+                                    // Start on the country being shown.
+                                    let selected = available.iter().position(|name| name == country_name).unwrap_or(0);
+                                    open_view(&mut view_state, &mut history, ViewState::SelectCountry { available, selected });
                                 }
                             }
                             KeyCode::Char('r') => {
-                                app_state = AppState::Loading;
-                                spawn_fetch_thread(tx.clone(), country_arc.clone(), client.clone());
+                                app_state = AppState::loading();
+                                spawn_fetch_thread(tx.clone(), country_arc.clone(), client.clone(), options.clone(), true);
+                            }
+                            KeyCode::Char('p') => {
+                                main_page.paused = !main_page.paused;
+                                if !main_page.paused {
+                                    resumed_at = Some(Instant::now());
+                                }
                             }
                             _ => {}
                         },
-                        ViewState::Details { scroll } => match key.code {
-                            KeyCode::Char('m') | KeyCode::Esc => view_state = ViewState::Main,
-                            KeyCode::Up => *scroll = scroll.saturating_sub(1),
-                            KeyCode::Down => *scroll = scroll.saturating_add(1),
+                        ViewState::Details { .. } => match key.code {
+                            KeyCode::Char('m') => go_home(&mut view_state, &mut history),
+                            KeyCode::Esc => go_back(&mut view_state, &mut history),
                             KeyCode::Char(c) => {
                                 if let Some(digit) = c.to_digit(10) {
                                     let index = digit as usize;
                                     if index > 0 && index <= data.country.regions.len() {
-                                        view_state = ViewState::Hourly { region_index: index - 1, scroll: 0 };
+                                        let hourly = ViewState::Hourly { region_index: index - 1, day: 0, scroll: 0, goto: None };
+                                        open_view(&mut view_state, &mut history, hourly);
                                     }
                                 }
                             }
                             _ => {}
                         },
-                        ViewState::Hourly { scroll, .. } => match key.code {
-                            KeyCode::Char('d') | KeyCode::Esc => view_state = ViewState::Details { scroll: 0 },
-                            KeyCode::Up => *scroll = scroll.saturating_sub(1),
-                            KeyCode::Down => *scroll = scroll.saturating_add(1),
+                        ViewState::Forecast { .. } => match key.code {
+                            KeyCode::Char('m') => go_home(&mut view_state, &mut history),
+                            KeyCode::Char('f') | KeyCode::Esc => go_back(&mut view_state, &mut history),
                             _ => {}
                         },
-                        ViewState::SelectCountry { available, scroll } => match key.code {
-                            KeyCode::Char('m') | KeyCode::Esc => view_state = ViewState::Main,
-                            KeyCode::Up => *scroll = scroll.saturating_sub(1),
-                            KeyCode::Down => *scroll = scroll.saturating_add(1),
+                        ViewState::Hourly { region_index, day, scroll, goto: Some(input) } => match key.code {
+                            KeyCode::Char(c) if (c.is_ascii_digit() || c == ':') && input.len() < 5 => input.push(c),
+                            KeyCode::Backspace => {
+                                input.pop();
+                            }
+                            KeyCode::Enter => {
+                                let index = wttr::parse_clock_time(input).and_then(|minutes| {
+                                    let report = data.reports.get(&data.country.regions.get(*region_index)?.name)?;
+                                    wttr::nearest_hourly_index(report.weather.get(*day)?, minutes)
+                                });
+                                if let Some(index) = index {
+                                    view_state = ViewState::Hourly {
+                                        region_index: *region_index,
+                                        day: *day,
+                                        scroll: index as u16,
+                                        goto: None,
+                                    };
+                                }
+                            }
+                            KeyCode::Esc => {
+                                view_state = ViewState::Hourly { region_index: *region_index, day: *day, scroll: *scroll, goto: None };
+                            }
+                            _ => {}
+                        },
+                        ViewState::Hourly { region_index, day, scroll, goto } => match key.code {
+                            KeyCode::Char('d') if matches!(history.last(), Some(ViewState::Details { .. })) => go_back(&mut view_state, &mut history),
+                            KeyCode::Char('d') => view_state = ViewState::Details { scroll: 0 },
+                            KeyCode::Esc => go_back(&mut view_state, &mut history),
+                            KeyCode::Char('g') => *goto = Some(String::new()),
+                            KeyCode::Char('[') => {
+                                *day = day.saturating_sub(1);
+                                *scroll = 0;
+                            }
+                            KeyCode::Char(']') => {
+                                let days = data.country.regions.get(*region_index)
+                                    .and_then(|region| data.reports.get(&region.name))
+                                    .map_or(0, |report| report.weather.len());
+                                if *day + 1 < days {
+                                    *day += 1;
+                                    *scroll = 0;
+                                }
+                            }
+                            _ => {}
+                        },
+                        ViewState::SelectCountry { available, selected } => match key.code {
+                            KeyCode::Char('m') => go_home(&mut view_state, &mut history),
+                            KeyCode::Esc => go_back(&mut view_state, &mut history),
+                            KeyCode::Enter => next_country = available.get(*selected).cloned(),
                             KeyCode::Char(c) => {
                                 if let Some(digit) = c.to_digit(10) {
                                     let index = digit as usize;
                                     if index > 0 && index <= available.len() {
-                                        return Ok(Some(available[index - 1].clone()));
+                                        next_country = Some(available[index - 1].clone());
                                     }
                                 }
                             }
                             _ => {}
                         },
+                        ViewState::BigIcon => {
+                            match key.code {
+                                KeyCode::Char('m') => go_home(&mut view_state, &mut history),
+                                KeyCode::Char('g') | KeyCode::Esc => go_back(&mut view_state, &mut history),
+                                _ => {}
+                            }
+                        }
+                        ViewState::ChangeLog { .. } => match key.code {
+                            KeyCode::Char('m') => go_home(&mut view_state, &mut history),
+                            KeyCode::Char('h') | KeyCode::Esc => go_back(&mut view_state, &mut history),
+                            _ => {}
+                        },
+                        ViewState::Ranking { .. } => match key.code {
+                            KeyCode::Char('m') => go_home(&mut view_state, &mut history),
+                            KeyCode::Char('n') | KeyCode::Esc => go_back(&mut view_state, &mut history),
+                            _ => {}
+                        },
                     },
-                    AppState::Loading => {
+                    AppState::Loading { .. } => {
                         if let KeyCode::Char('q') | KeyCode::Esc = key.code {
                             return Ok(None);
                         }
@@ -186,29 +969,373 @@ pub fn run_app(
             }
         }
 
-        if let Ok(result) = rx.try_recv() {
+        if cli.transitions && std::mem::discriminant(&view_state) != shown_view {
+            reveal_started = Some(Instant::now());
+        }
+
+        if main_page.notice.is_some() && notice_shown_at.elapsed() > config::NOTICE_DURATION {
+            main_page.notice = None;
+        }
+
+        if let Some(new_country) = next_country {
+            if cli.keep_countries_cached {
+                if let AppState::Loaded { data, updated_at, last_fetch } = app_state {
+                    cache.insert(country_name.to_string(), CachedCountry { data, updated_at, last_fetch });
+                }
+            }
+            return Ok(Some(new_country));
+        }
+
+        while let Ok(message) = rx.try_recv() {
+            let result = match message {
+                FetchMessage::Progress { region, city } => {
+                    if let AppState::Loading { fetching, .. } = &mut app_state {
+                        *fetching = Some(format!("{} ({})", region, city));
+                    }
+                    continue;
+                }
+                FetchMessage::Finished(result) => result,
+            };
             match result {
-                Ok(data) => {
+                Ok(mut data) => {
+                    metrics.record_success(country_name, &data);
+                    let pressures = pressures_by_region(&data);
+                    data.pressure_trends = pressures
+                        .iter()
+                        .filter_map(|(name, &pressure)| {
+                            Some((name.clone(), wttr::pressure_trend(*previous_pressures.get(name)?, pressure)))
+                        })
+                        .collect();
+                    previous_pressures = pressures;
+                    let conditions = conditions_by_region(&data);
+                    let time = Local::now().format("%H:%M");
+                    for change in condition_changes(&previous_conditions, &conditions, &data.country.regions) {
+                        change_log.push_front(format!("{} {}", time, change));
+                    }
+                    change_log.truncate(config::CHANGE_LOG_LIMIT);
+                    previous_conditions = conditions;
                     app_state = AppState::Loaded {
                         data,
                         updated_at: Local::now(),
                         last_fetch: Instant::now(),
                     }
                 }
-                Err(e) => app_state = AppState::Error(e),
+                // Only a background refresh finishes while a page is shown; keep showing it.
+                Err(e) if matches!(app_state, AppState::Loaded { .. }) => {
+                    metrics.record_failure();
+                    main_page.notice = Some(format!("Background refresh failed: {}", e.lines().next().unwrap_or_default()));
+                    notice_shown_at = Instant::now();
+                }
+                Err(e) => {
+                    metrics.record_failure();
+                    app_state = AppState::Error(e);
+                }
             }
         }
 
+        // Guards against a wedged fetch thread; a late result still replaces the error.
+        if app_state.loading_timed_out(cli.loading_deadline()) {
+            metrics.record_failure();
+            app_state = AppState::Error("Timed out waiting for weather data".to_string());
+        }
+
         if let AppState::Loaded { ref mut last_fetch, .. } = app_state {
-            if last_fetch.elapsed() > config::REFRESH_INTERVAL {
-                app_state = AppState::Loading;
-                spawn_fetch_thread(tx.clone(), country_arc.clone(), client.clone());
+            let since_resume = resumed_at.map(|resumed| resumed.elapsed());
+            if refresh_remaining(last_fetch.elapsed(), since_resume, cli.refresh, main_page.paused).is_some_and(|left| left.is_zero()) {
+                app_state = AppState::loading();
+                spawn_fetch_thread(tx.clone(), country_arc.clone(), client.clone(), options.clone(), false);
             }
         }
 
-        if matches!(app_state, AppState::Loading) {
+        if matches!(app_state, AppState::Loading { .. }) {
             counter = 100 + (counter + 1 - 100) % 800;
         }
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wttr::WeatherClient;
+    use clap::Parser;
+
+    fn uk_country() -> Arc<config::Country> {
+        Arc::new(config::parse_country(include_str!("../templates/uk.toml")).unwrap())
+    }
+
+    /// Answers every UK region's city at a temperature of its own, except the cities in `missing`,
+    /// which fail as unknown locations.
+    fn uk_client(missing: &[&str]) -> wttr::MockWeatherClient {
+        let temps = [("London", 21), ("Cardiff", 17), ("Manchester", 14), ("Edinburgh", 11), ("Belfast", -3)];
+        let answered: Vec<(&str, i32)> = temps.into_iter().filter(|(city, _)| !missing.contains(city)).collect();
+        wttr::MockWeatherClient::with_temps(&answered)
+    }
+
+    /// Draws one frame at the preview size.
+    fn render(draw: impl FnOnce(&mut ratatui::Frame)) -> ratatui::buffer::Buffer {
+        let mut terminal = Terminal::new(TestBackend::new(PREVIEW_SIZE.0, PREVIEW_SIZE.1)).unwrap();
+        terminal.draw(draw).unwrap().buffer.clone()
+    }
+
+    /// The screen's rows, without trailing blanks.
+    fn rows(buffer: &ratatui::buffer::Buffer) -> Vec<String> {
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_arrows_and_vim_keys_move_lists() {
+        let extent = ui::ListExtent { rows: 30, visible: 10 };
+        let mut details = ViewState::Details { scroll: 0 };
+        assert!(details.scroll_by_key(KeyCode::Char('j'), extent));
+        assert!(details.scroll_by_key(KeyCode::Down, extent));
+        assert!(details.scroll_by_key(KeyCode::Char('k'), extent));
+        assert!(matches!(details, ViewState::Details { scroll: 1 }));
+        // Digits are left for the views' own shortcuts.
+        assert!(!details.scroll_by_key(KeyCode::Char('1'), extent));
+
+        let mut picker = ViewState::SelectCountry { available: vec!["uk".to_string(), "usa".to_string()], selected: 0 };
+        picker.scroll_by_key(KeyCode::Char('j'), extent);
+        picker.scroll_by_key(KeyCode::Char('j'), extent);
+        assert!(matches!(picker, ViewState::SelectCountry { selected: 1, .. }));
+
+        let mut typing = ViewState::Hourly { region_index: 0, day: 0, scroll: 0, goto: Some(String::new()) };
+        assert!(!typing.scroll_by_key(KeyCode::Char('j'), extent));
+        assert!(!ViewState::Main.scroll_by_key(KeyCode::Char('k'), extent));
+    }
+
+    #[test]
+    fn test_loading_times_out_after_the_deadline() {
+        let deadline = Duration::from_secs(30);
+        assert!(!AppState::loading().loading_timed_out(deadline));
+        let stuck = AppState::Loading { started: Instant::now() - Duration::from_secs(31), fetching: None };
+        assert!(stuck.loading_timed_out(deadline));
+        assert!(!AppState::Error("offline".to_string()).loading_timed_out(Duration::ZERO));
+    }
+
+    #[test]
+    fn test_esc_retraces_the_views_opened() {
+        let mut view = ViewState::Main;
+        let mut history = Vec::new();
+        open_view(&mut view, &mut history, ViewState::Details { scroll: 4 });
+        open_view(&mut view, &mut history, ViewState::Hourly { region_index: 1, day: 0, scroll: 0, goto: None });
+        go_back(&mut view, &mut history);
+        // The details page comes back scrolled as it was left.
+        assert!(matches!(view, ViewState::Details { scroll: 4 }));
+        go_back(&mut view, &mut history);
+        assert!(matches!(view, ViewState::Main));
+        assert!(history.is_empty());
+
+        // Going back from a start view with no history, or again from Main, stays on Main.
+        let mut view = ViewState::Forecast { scroll: 0 };
+        go_back(&mut view, &mut history);
+        assert!(matches!(view, ViewState::Main));
+        go_back(&mut view, &mut history);
+        assert!(matches!(view, ViewState::Main));
+
+        open_view(&mut view, &mut history, ViewState::ChangeLog { scroll: 0 });
+        open_view(&mut view, &mut history, ViewState::BigIcon);
+        go_home(&mut view, &mut history);
+        assert!(matches!(view, ViewState::Main));
+        assert!(history.is_empty());
+    }
+
+    #[test]
+    fn test_paused_auto_refresh_waits_a_full_interval_after_resuming() {
+        let mins = |m: u64| Duration::from_secs(m * 60);
+        assert_eq!(refresh_remaining(mins(5), None, mins(15), false), Some(mins(10)));
+        assert_eq!(refresh_remaining(mins(20), None, mins(15), false), Some(Duration::ZERO));
+        assert_eq!(refresh_remaining(mins(20), None, mins(15), true), None);
+        // Resumed two minutes ago, long after the interval ran out.
+        assert_eq!(refresh_remaining(mins(40), Some(mins(2)), mins(15), false), Some(mins(13)));
+        // A fetch since resuming restarts the interval from the fetch.
+        assert_eq!(refresh_remaining(mins(1), Some(mins(30)), mins(15), false), Some(mins(14)));
+    }
+
+    #[test]
+    fn test_headless_runs_retry_failures_sooner_and_stop_at_exit_after() {
+        let mut cli = config::Cli::parse_from(["ceefax-weather", "--exit-after", "30m"]);
+        let long_ago = Instant::now() - Duration::from_secs(2 * 60);
+        assert!(wait_for_refresh(&cli, long_ago, true));
+        cli.started -= Duration::from_secs(31 * 60);
+        assert!(!wait_for_refresh(&cli, long_ago, false));
+    }
+
+    #[test]
+    fn test_selection_stops_at_the_ends_of_the_list() {
+        assert_eq!(ListKey::Down.apply(0, 2, 1), 1);
+        assert_eq!(ListKey::Down.apply(2, 2, 1), 2);
+        assert_eq!(ListKey::Up.apply(1, 2, 1), 0);
+        assert_eq!(ListKey::Up.apply(0, 2, 1), 0);
+        assert_eq!(ListKey::Down.apply(0, 0, 1), 0);
+    }
+
+    #[test]
+    fn test_paging_keys_are_clamped_to_the_list() {
+        assert_eq!(ListKey::PageDown.apply(0, 25, 10), 10);
+        assert_eq!(ListKey::PageDown.apply(20, 25, 10), 25);
+        assert_eq!(ListKey::PageUp.apply(15, 25, 10), 5);
+        assert_eq!(ListKey::PageUp.apply(5, 25, 10), 0);
+        assert_eq!(ListKey::Home.apply(17, 25, 10), 0);
+        assert_eq!(ListKey::End.apply(3, 25, 10), 25);
+        // An unknown screen height still moves a row at a time.
+        assert_eq!(ListKey::PageDown.apply(3, 25, 0), 4);
+
+        // Scrolling stops once the last of 30 rows fills the bottom of a 10-row screen.
+        let extent = ui::ListExtent { rows: 30, visible: 10 };
+        let mut hourly = ViewState::Hourly { region_index: 0, day: 0, scroll: 0, goto: None };
+        assert!(hourly.scroll_by_key(KeyCode::End, extent));
+        assert!(matches!(hourly, ViewState::Hourly { scroll: 20, .. }));
+        hourly.scroll_by_key(KeyCode::Down, extent);
+        assert!(matches!(hourly, ViewState::Hourly { scroll: 20, .. }));
+        hourly.scroll_by_key(KeyCode::PageUp, extent);
+        assert!(matches!(hourly, ViewState::Hourly { scroll: 10, .. }));
+
+        let mut picker = ViewState::SelectCountry { available: vec!["a".to_string(); 12], selected: 0 };
+        picker.scroll_by_key(KeyCode::End, ui::ListExtent { rows: 12, visible: 5 });
+        assert!(matches!(picker, ViewState::SelectCountry { selected: 11, .. }));
+    }
+
+    #[test]
+    fn test_condition_changes_are_described() {
+        let region = |name: &str, city: &str| config::Region {
+            name: name.to_string(),
+            city: city.to_string(),
+            char: 'X',
+            ..Default::default()
+        };
+        let regions = vec![region("S. England", "London"), region("Wales", "Cardiff"), region("Scotland", "Edinburgh")];
+        let conditions = |pairs: &[(&str, &str)]| -> HashMap<String, String> {
+            pairs.iter().map(|(name, desc)| (name.to_string(), desc.to_string())).collect()
+        };
+        let previous = conditions(&[("S. England", "Sunny"), ("Wales", "Overcast")]);
+        let current = conditions(&[("S. England", "Light rain"), ("Wales", "Overcast"), ("Scotland", "Mist")]);
+
+        assert_eq!(condition_changes(&previous, &current, &regions), vec!["London: Sunny → Light rain"]);
+        assert!(condition_changes(&HashMap::new(), &current, &regions).is_empty());
+    }
+
+    #[test]
+    fn test_extra_cities_are_averaged_or_represented() {
+        let client = wttr::MockWeatherClient::with_temps(&[("Inverness", 4), ("Glasgow", 9), ("Edinburgh", 10)]);
+        let mut region = config::Region {
+            name: "Scotland".to_string(),
+            city: "Edinburgh".to_string(),
+            char: 'S',
+            cities: vec!["Inverness".to_string(), "Glasgow".to_string(), "Atlantis".to_string()],
+            aggregate: config::Aggregation::Average,
+            ..Default::default()
+        };
+        let current = |region: &config::Region| {
+            let report = with_extra_cities(&client, region, client.fetch(&region.city).unwrap()).unwrap();
+            let condition = report.current_condition[0].clone();
+            (condition.temp_C, condition.weatherDesc[0].value.clone())
+        };
+
+        // (10 + 4 + 9) / 3 rounds to 8, keeping the main city's conditions; Atlantis is skipped.
+        assert_eq!(current(&region), ("8".to_string(), "Edinburgh".to_string()));
+
+        region.aggregate = config::Aggregation::Representative;
+        assert_eq!(current(&region), ("9".to_string(), "Glasgow".to_string()));
+    }
+
+    #[test]
+    fn test_stream_writes_a_record_and_stops_at_exit_after() {
+        let mut cli = config::Cli::parse_from(["ceefax-weather", "--stream", "--exit-after", "30m"]);
+        cli.started -= Duration::from_secs(31 * 60);
+        let mut out = Vec::new();
+
+        run_stream("uk", (*uk_country()).clone(), Arc::new(uk_client(&[])), &cli, &metrics::Metrics::default(), &mut out).unwrap();
+
+        let lines: Vec<&str> = std::str::from_utf8(&out).unwrap().lines().collect();
+        assert_eq!(lines.len(), 1);
+        let record: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(record["country"], "uk");
+        assert_eq!(record["regions"].as_array().unwrap().len(), 5);
+        assert_eq!(record["regions"][0]["city"], "London");
+        assert_eq!(record["regions"][0]["temp_c"], 21);
+    }
+
+    #[test]
+    fn test_regions_are_fetched_concurrently_and_kept_in_order() {
+        let country = uk_country();
+        let client = uk_client(&[]).taking(Duration::from_millis(50));
+
+        let data = fetch_country_data(country.clone(), &client, &config::DisplayOptions::default(), |_| {}).unwrap();
+
+        assert!(client.most_in_flight() > 1, "regions were fetched one at a time");
+        assert_eq!(data.reports.len(), country.regions.len());
+        let order: Vec<&str> = data.summaries.iter().map(|summary| summary.region.as_str()).collect();
+        let expected: Vec<&str> = country.regions.iter().map(|region| region.name.as_str()).collect();
+        assert_eq!(order, expected);
+    }
+
+    #[test]
+    fn test_failed_regions_leave_the_rest_on_the_page() {
+        let country = uk_country();
+        let options = config::DisplayOptions::default();
+        let client = uk_client(&["Manchester", "Belfast"]);
+
+        let data = fetch_country_data(country.clone(), &client, &options, |_| {}).unwrap();
+        assert_eq!(data.reports.len(), 3);
+        let mut failed: Vec<&str> = data.failures.keys().map(String::as_str).collect();
+        failed.sort();
+        assert_eq!(failed, vec!["N. England", "N. Ireland"]);
+        assert!(data.failures["N. Ireland"].contains("Unknown location"));
+
+        let screen = rows(&render(|f| ui::main_ui(f, &data, &options, &Local::now(), &ui::MainPageState::default()))).concat();
+        assert!(screen.contains("N/A"));
+        assert!(screen.contains("21"));
+
+        // Only when nothing at all could be fetched is it an error.
+        let err = fetch_country_data(country, &wttr::MockWeatherClient::default(), &options, |_| {}).err().unwrap();
+        assert!(err.contains("Unknown location 'London'"), "{}", err);
+    }
+
+    #[test]
+    fn test_ranking_page_lists_warmest_first() {
+        let options = config::DisplayOptions::default();
+        let data = fetch_country_data(uk_country(), &uk_client(&["Manchester"]), &options, |_| {}).unwrap();
+
+        let rows = rows(&render(|f| {
+            ui::ranking_ui(f, &data, &options, 0);
+        }));
+
+        assert_eq!(rows[1], " 1. S. England   21°C  London");
+        assert_eq!(rows[2], " 2. Wales        17°C  Cardiff");
+        assert_eq!(rows[3], " 3. Scotland     11°C  Edinburgh");
+        assert_eq!(rows[4], " 4. N. Ireland   -3°C  Belfast");
+        // N. England failed, so it isn't ranked.
+        assert_eq!(rows[5], "");
+    }
+
+    #[test]
+    fn test_fetch_to_render_pipeline_for_uk() {
+        let country = uk_country();
+        let client = uk_client(&[]);
+        let options = config::DisplayOptions::default();
+
+        let mut reached = Vec::new();
+        let data = fetch_country_data(country.clone(), &client, &options, |region| reached.push(region.name.clone())).unwrap();
+        assert_eq!(reached.first(), Some(&country.regions[0].name));
+
+        let page = ui::MainPageState::default();
+        let buffer = render(|f| ui::main_ui(f, &data, &options, &Local::now(), &page));
+        let screen = rows(&buffer).concat();
+
+        // The mock describes each region's weather by its city's name.
+        for region in &country.regions {
+            let summary = format!("{}: {}", region.name, region.city);
+            assert!(screen.contains(&summary), "{} missing from the summary panel", summary);
+        }
+        // Each temperature is written over its own region of the map, not just anywhere on screen
+        // where the header's date and clock could match it.
+        let cells = |x: u16, y: u16, len: u16| (x..x + len).map(|x| buffer.get(x, y).symbol()).collect::<String>();
+        for (x, y, label) in [(58, 12, "21"), (48, 10, "17"), (56, 9, "14"), (56, 7, "11"), (45, 7, "-3")] {
+            assert_eq!(cells(x, y, label.len() as u16), label, "{}° missing from its region", label);
+        }
+    }
+}
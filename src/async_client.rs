@@ -0,0 +1,137 @@
+use crate::{
+    cache::DiskCache,
+    config,
+    wttr::{self, Backoff, FetchFailure, WeatherClient, WeatherReport},
+};
+use futures_util::future::join_all;
+use std::time::Duration;
+
+/// A wttr.in client that runs its requests on a tokio runtime, so a whole country can be
+/// fetched concurrently. The TUI still calls it synchronously.
+pub struct AsyncLiveWeatherClient {
+    runtime: tokio::runtime::Runtime,
+    client: reqwest::Client,
+    cache: Option<DiskCache>,
+    options: config::FetchOptions,
+}
+
+impl AsyncLiveWeatherClient {
+    /// Builds the client and its runtime.
+    pub fn new(cache: Option<DiskCache>, options: &config::FetchOptions) -> Result<Self, String> {
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| format!("Failed to start the fetch runtime: {}", e))?;
+        let mut builder = reqwest::Client::builder().timeout(options.timeout);
+        if let Some(url) = &options.proxy {
+            let proxy = reqwest::Proxy::all(url).map_err(|e| format!("Invalid proxy URL '{}': {}", url, e))?;
+            builder = builder.proxy(proxy);
+        }
+        let client = builder
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        Ok(Self {
+            runtime,
+            client,
+            cache,
+            options: options.clone(),
+        })
+    }
+
+    async fn request(&self, city: &str) -> Result<String, FetchFailure> {
+        let url = format!("{}?format=j1", wttr::location_url(city));
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| FetchFailure::Transient(wttr::request_error(e, &self.options)))?;
+        if !response.status().is_success() {
+            return Err(wttr::status_failure(response.status()));
+        }
+
+        response.text().await.map_err(|e| FetchFailure::Transient(wttr::body_error(e, &self.options)))
+    }
+
+    /// Fetches `city`, answering from the cache first unless `fresh` is set.
+    async fn fetch_async(&self, city: &str, fresh: bool) -> Result<WeatherReport, String> {
+        if let Some((report, _)) = self.cached(city).filter(|_| !fresh) {
+            return Ok(report);
+        }
+        // The same schedule as `wttr::retry_with_backoff`, without blocking a runtime thread.
+        let mut backoff = Backoff::new(self.options.retries);
+        let (report, text) = loop {
+            let attempt = self
+                .request(city)
+                .await
+                .and_then(|text| wttr::parse_report(&text).map(|report| (report, text)).map_err(FetchFailure::Permanent));
+            match attempt {
+                Ok(fetched) => break fetched,
+                Err(failure) => tokio::time::sleep(backoff.after(failure)?).await,
+            }
+        };
+        if let Some(cache) = &self.cache {
+            // A failed cache write only costs us a refetch next time.
+            let _ = cache.store(city, &text);
+        }
+        Ok(report)
+    }
+}
+
+impl WeatherClient for AsyncLiveWeatherClient {
+    fn fetch(&self, city: &str) -> Result<WeatherReport, String> {
+        self.runtime.block_on(self.fetch_async(city, false))
+    }
+
+    fn fetch_many(&self, cities: &[&str]) -> Option<Vec<Result<WeatherReport, String>>> {
+        Some(self.runtime.block_on(join_all(cities.iter().map(|city| self.fetch_async(city, false)))))
+    }
+
+    fn fetch_fresh(&self, city: &str) -> Result<WeatherReport, String> {
+        self.runtime.block_on(self.fetch_async(city, true))
+    }
+
+    fn fetch_many_fresh(&self, cities: &[&str]) -> Option<Vec<Result<WeatherReport, String>>> {
+        Some(self.runtime.block_on(join_all(cities.iter().map(|city| self.fetch_async(city, true)))))
+    }
+
+    fn cached(&self, city: &str) -> Option<(WeatherReport, Duration)> {
+        let (body, age) = self.cache.as_ref()?.load(city)?;
+        wttr::parse_report(&body).ok().map(|report| (report, age))
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "wttr.in"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fetch_many_keeps_order_and_reports_each_failure() {
+        let dir = std::env::temp_dir().join(format!("ceefax-weather-test-async-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let cache = DiskCache::new(dir.clone(), Duration::from_secs(60));
+        for (city, temp) in [("Leeds", "9"), ("York", "7")] {
+            cache.store(city, &wttr::MockWeatherClient::body(temp, "SW", "Cloudy")).unwrap();
+        }
+        // Nothing listens here, so any request that reaches the network fails at once.
+        let options = config::FetchOptions {
+            proxy: Some("http://127.0.0.1:9".to_string()),
+            retries: 1,
+            ..Default::default()
+        };
+        let client = AsyncLiveWeatherClient::new(Some(cache), &options).unwrap();
+
+        let results = client.fetch_many(&["York", "Hull", "Leeds"]).unwrap();
+        assert_eq!(results[0].as_ref().unwrap().current_condition[0].temp_C, "7");
+        assert!(results[1].as_ref().unwrap_err().contains("proxy"));
+        assert_eq!(results[2].as_ref().unwrap().current_condition[0].temp_C, "9");
+
+        // A forced fetch goes past the cache to the network.
+        assert!(client.fetch_many_fresh(&["Leeds"]).unwrap()[0].is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::wttr;
+use serde::{Deserialize, Serialize};
+use std::{
+    env, fs, io,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry {
+    fetched_at: u64,
+    body: String,
+}
+
+/// Raw API responses stored on disk, one JSON file per city.
+pub struct DiskCache {
+    dir: PathBuf,
+    ttl: Duration,
+}
+
+impl DiskCache {
+    pub fn new(dir: PathBuf, ttl: Duration) -> Self {
+        Self { dir, ttl }
+    }
+
+    /// `$XDG_CACHE_HOME/ceefax-weather`, falling back to `~/.cache/ceefax-weather`.
+    pub fn default_dir() -> Option<PathBuf> {
+        let base = env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))?;
+        Some(base.join("ceefax-weather"))
+    }
+
+    fn path_for(&self, city: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", wttr::encode_location(city)))
+    }
+
+    /// Returns the cached body for `city` and its age, if it is younger than the TTL.
+    pub fn load(&self, city: &str) -> Option<(String, Duration)> {
+        let contents = fs::read_to_string(self.path_for(city)).ok()?;
+        let entry: CacheEntry = serde_json::from_str(&contents).ok()?;
+        let fetched_at = UNIX_EPOCH + Duration::from_secs(entry.fetched_at);
+        let age = SystemTime::now().duration_since(fetched_at).unwrap_or_default();
+        (age < self.ttl).then_some((entry.body, age))
+    }
+
+    pub fn store(&self, city: &str, body: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let fetched_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let entry = CacheEntry {
+            fetched_at,
+            body: body.to_string(),
+        };
+        fs::write(self.path_for(city), serde_json::to_string(&entry)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_cache_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("ceefax-weather-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    #[test]
+    fn test_store_then_load_round_trip() {
+        let dir = temp_cache_dir("round-trip");
+        let cache = DiskCache::new(dir.clone(), Duration::from_secs(60));
+
+        assert!(cache.load("New York").is_none());
+        cache.store("New York", "{\"ok\":true}").unwrap();
+        let (body, age) = cache.load("New York").unwrap();
+        assert_eq!(body, "{\"ok\":true}");
+        assert!(age < Duration::from_secs(60));
+
+        let expired = DiskCache::new(dir.clone(), Duration::ZERO);
+        assert!(expired.load("New York").is_none());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
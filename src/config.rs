@@ -1,45 +1,815 @@
-use clap::Parser;
+use clap::{parser::ValueSource, CommandFactory, Parser, ValueEnum};
 use ratatui::style::Color;
 use serde::Deserialize;
-use std::{env, fs, io, time::Duration};
+use std::{
+    env, fs, io,
+    net::IpAddr,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
 
 // --- CEEFAX Color Palette ---
 pub const CEEFAX_BLUE: Color = Color::Rgb(0, 0, 170);
 pub const CEEFAX_GREEN: Color = Color::Rgb(0, 204, 0);
 pub const CEEFAX_CYAN: Color = Color::Rgb(0, 204, 204);
 pub const CEEFAX_YELLOW: Color = Color::Rgb(204, 204, 0);
+pub const CEEFAX_RED: Color = Color::Rgb(204, 0, 0);
 pub const CEEFAX_WHITE: Color = Color::Rgb(255, 255, 255);
 pub const CEEFAX_BLACK: Color = Color::Rgb(0, 0, 0);
+pub const CEEFAX_MAGENTA: Color = Color::Rgb(204, 0, 204);
+/// Highlight for the sunset countdown in its final hour, and the "High" UV band.
+pub const SUNSET_ORANGE: Color = Color::Rgb(255, 136, 0);
+/// Map background for land whose region has no data, e.g. when filtered out by `--regions`.
+pub const NO_DATA_GREY: Color = Color::Rgb(128, 128, 128);
+
+pub const CEEFAX_PALETTE: [(&str, Color); 7] = [
+    ("BLUE", CEEFAX_BLUE),
+    ("GREEN", CEEFAX_GREEN),
+    ("CYAN", CEEFAX_CYAN),
+    ("YELLOW", CEEFAX_YELLOW),
+    ("RED", CEEFAX_RED),
+    ("WHITE", CEEFAX_WHITE),
+    ("BLACK", CEEFAX_BLACK),
+];
 
 // --- Unicode Teletext Mosaic Characters ---
 pub const TELETEXT_CHARS: [char; 16] = [
     ' ', '▘', '▝', '▀', '▖', '▌', '▞', '▛', '▗', '▚', '▐', '▜', '▄', '▙', '▟', '█',
 ];
+// Denser-is-darker fallbacks for terminals without the quadrant blocks, by land quadrant count.
+pub const SHADE_CHARS: [char; 16] = [
+    ' ', '░', '░', '▒', '░', '▒', '▒', '▓', '░', '▒', '▒', '▓', '▒', '▓', '▓', '█',
+];
+pub const ASCII_CHARS: [char; 16] = [
+    ' ', '.', '.', '+', '.', '+', '+', '#', '.', '+', '+', '#', '+', '#', '#', '#',
+];
 
 // --- Application Configuration ---
-pub const REFRESH_INTERVAL: Duration = Duration::from_secs(15 * 60); // 15 minutes
+/// Bounds on `--refresh`.
+pub const MIN_REFRESH: Duration = Duration::from_secs(30);
+pub const MAX_REFRESH: Duration = Duration::from_secs(24 * 60 * 60);
+pub const SLOW_FETCH_THRESHOLD: Duration = Duration::from_secs(8);
+pub const NOTICE_DURATION: Duration = Duration::from_secs(10);
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Wait before the first retry of a failed request; doubled for each retry after.
+pub const RETRY_BASE_DELAY: Duration = Duration::from_millis(200);
+/// How soon a run without a terminal tries again after a failed fetch, if sooner than `--refresh`.
+pub const ERROR_RETRY_INTERVAL: Duration = Duration::from_secs(60);
+/// Shortest default for `--loading-timeout`, however quick the request settings allow a fetch to be.
+pub const MIN_LOADING_TIMEOUT: Duration = Duration::from_secs(60);
+/// How long a new page takes to paint in with `--transitions`.
+pub const REVEAL_DURATION: Duration = Duration::from_millis(200);
+/// Items a list can offer for selection with the digit keys 1-9.
+pub const DIGIT_KEYS: usize = 9;
+/// Widest condition description shown in the summary panel and footer.
+pub const MAX_DESC_WIDTH: usize = 32;
+/// Entries kept in the P188 condition change log.
+pub const CHANGE_LOG_LIMIT: usize = 50;
+/// Hourly slots shown in the main page's "coming up" strip.
+pub const OUTLOOK_SLOTS: usize = 5;
 
 // --- Command Line Argument Parsing ---
+pub const ARGS_ENV_VAR: &str = "CEEFAX_WEATHER_ARGS";
+
 #[derive(Parser, Clone)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_override_self = true)]
 pub struct Cli {
-    #[arg(short, long, value_name = "COUNTRY", default_value = "uk")]
-    pub country: String,
+    /// Country to show; defaults to the last one chosen from the country menu, or `uk`.
+    #[arg(short, long, value_name = "COUNTRY")]
+    pub country: Option<String>,
+
+    /// Start with the country defined in this TOML file instead of one from `templates/`.
+    #[arg(long, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Replace emoji icons and color-only cues with plain words, for screen readers.
+    #[arg(long)]
+    pub text_only: bool,
+
+    /// Keep fetched data for each country in memory and reuse it when switching back.
+    #[arg(long)]
+    pub keep_countries_cached: bool,
+
+    /// Serve Prometheus metrics over HTTP on this port.
+    #[arg(long, value_name = "PORT")]
+    pub metrics_port: Option<u16>,
+
+    /// Address the metrics server listens on; only this machine can reach it by default.
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1")]
+    pub metrics_address: IpAddr,
+
+    /// The page to show once the first fetch completes.
+    #[arg(long, value_enum, value_name = "VIEW", default_value_t = StartView::Main)]
+    pub start_view: StartView,
+
+    /// Auto-refresh interval, e.g. `30s`, `5m` or `1h` (a bare number means minutes).
+    #[arg(long, visible_alias = "refresh-mins", value_name = "DURATION", default_value = "15m", value_parser = parse_refresh)]
+    pub refresh: Duration,
+
+    /// Mark data as STALE once it is older than this; defaults to twice the refresh interval.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub stale_after: Option<Duration>,
+
+    /// Where weather data comes from.
+    #[arg(long, value_enum, default_value_t = Provider::Wttr)]
+    pub provider: Provider,
+
+    /// HTTP(S) proxy URL; takes precedence over HTTP_PROXY/HTTPS_PROXY.
+    #[arg(long, value_name = "URL")]
+    pub proxy: Option<String>,
+
+    /// Times to retry a region after a network error, backing off from 200ms and doubling.
+    #[arg(long, value_name = "N", default_value_t = 3)]
+    pub retries: u32,
+
+    /// Give up on a request that hasn't completed after this many seconds.
+    #[arg(long, value_name = "SECS", default_value_t = DEFAULT_TIMEOUT.as_secs())]
+    pub timeout_secs: u64,
+
+    /// Show an error if a whole fetch, retries included, is still loading after this long;
+    /// defaults to enough time for a request to use up every retry, and at least a minute.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub loading_timeout: Option<Duration>,
+
+    /// Always fetch, even if the disk cache is still fresh; the cache is still updated.
+    #[arg(long)]
+    pub force_refresh: bool,
+
+    /// After starting from fresh cached reports, fetch new ones in the background straight away.
+    #[arg(long, conflicts_with = "force_refresh")]
+    pub background_refresh: bool,
+
+    /// Neither read nor write the on-disk report cache.
+    #[arg(long)]
+    pub no_cache: bool,
+
+    /// How long a cached report is reused instead of fetched; defaults to the refresh interval.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub cache_ttl: Option<Duration>,
+
+    /// Temperature and wind speed units; imperial shows °F and mph everywhere, map included.
+    #[arg(long, value_enum, value_name = "UNITS", default_value_t = Units::Metric)]
+    pub units: Units,
+
+    /// Leave the terminal title alone instead of showing the featured region's weather.
+    #[arg(long)]
+    pub no_title_update: bool,
+
+    /// Show the header date as formatted by the locale rather than in capitals.
+    #[arg(long)]
+    pub no_uppercase_date: bool,
+
+    /// Show how long the last fetch took in the main page footer.
+    #[arg(long)]
+    pub show_fetch_time: bool,
+
+    /// Condition icons; `ascii` or `nerdfont` avoid misaligned emoji on some terminals.
+    #[arg(long, value_enum, value_name = "SET", default_value_t = IconSet::Emoji)]
+    pub icons: IconSet,
+
+    /// Show the data source in the main page footer (toggle with `a`).
+    #[arg(long)]
+    pub attribution: bool,
+
+    /// Quit cleanly after this long, e.g. `10m`, so a signage scheduler can cycle displays.
+    #[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+    pub exit_after: Option<Duration>,
+
+    /// When the process started, for `--exit-after`.
+    #[arg(skip = Instant::now())]
+    pub started: Instant,
+
+    /// Scale the map to fit its pane instead of drawing two template cells per character.
+    #[arg(long)]
+    pub fit_map: bool,
+
+    /// Mark this region on the map so it is easy to find, e.g. `Scotland`.
+    #[arg(long, value_name = "NAME")]
+    pub home_region: Option<String>,
+
+    /// Region whose summary is pinned to the top of the list; `t` then a number changes it.
+    #[arg(long, value_name = "REGION")]
+    pub pin_region: Option<String>,
+
+    /// Only fetch and show these regions, e.g. `Scotland,Wales`; the rest of the map is greyed out.
+    #[arg(long, value_name = "NAMES", value_delimiter = ',')]
+    pub regions: Vec<String>,
+
+    /// Run without a terminal, printing one JSON line of conditions per refresh.
+    #[arg(long, conflicts_with = "serve")]
+    pub stream: bool,
+
+    /// Run without a terminal and serve the rendered main page as HTML on this port.
+    #[arg(long, value_name = "PORT")]
+    pub serve: Option<u16>,
+
+    /// Address the preview server listens on; only this machine can reach it by default.
+    #[arg(long, value_name = "ADDR", default_value = "127.0.0.1")]
+    pub serve_address: IpAddr,
+
+    /// Fetch all regions on an async runtime instead of a thread per region (wttr.in only).
+    #[cfg(feature = "async")]
+    #[arg(long)]
+    pub concurrent_fetch: bool,
+
+    /// Map glyphs: `mosaic`, `shade`, `ascii`, or 16 characters indexed by the 2x2 land bitmask.
+    #[arg(long, value_name = "SET", default_value = "mosaic", value_parser = parse_map_glyphs)]
+    pub map_glyphs: MapGlyphs,
+
+    /// Write temperatures over the map: always, never, or only where they fit.
+    #[arg(long, value_enum, default_value_t = Overlay::Always)]
+    pub overlay: Overlay,
+
+    /// Draw the map as plain land and sea, without temperature colors or labels.
+    #[arg(long)]
+    pub outline_only: bool,
+
+    /// Paint each new page in top-to-bottom when switching views, like a teletext set.
+    #[arg(long)]
+    pub transitions: bool,
+
+    /// Palette to render with; detected from $COLORTERM when omitted.
+    #[arg(long, value_enum, value_name = "MODE")]
+    pub color_mode: Option<ColorMode>,
+
+    /// Color theme: the broadcast CEEFAX colors, an amber terminal, or high-contrast monochrome.
+    #[arg(long, value_enum, value_name = "THEME", default_value_t = ThemeName::Ceefax)]
+    pub theme: ThemeName,
+
+    /// Render without any colors or styling; also set by a non-empty $NO_COLOR.
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Show palette swatches to check the terminal's color support, then exit.
+    #[arg(long, hide = true)]
+    pub colortest: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, ValueEnum)]
+pub enum StartView {
+    Main,
+    Details,
+    /// The national temperature ranking, P186.
+    Ranking,
+    /// The multi-day forecast, P185.
+    #[value(alias = "outlook")]
+    Forecast,
+    Picture,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, ValueEnum)]
+pub enum Provider {
+    #[default]
+    Wttr,
+    /// Open-Meteo, with cities located through its geocoding API.
+    OpenMeteo,
+}
+
+/// When temperatures are written over the map.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, ValueEnum)]
+pub enum Overlay {
+    /// Only where the region is wide enough on screen to hold the digits.
+    Auto,
+    #[default]
+    Always,
+    /// Color only.
+    Never,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, ValueEnum)]
+pub enum Units {
+    #[default]
+    Metric,
+    Imperial,
+    /// Celsius and Fahrenheit side by side.
+    Both,
+}
+
+impl Units {
+    /// The scale single temperatures, such as map labels, are shown in.
+    pub fn scale(self) -> crate::wttr::TempScale {
+        match self {
+            Units::Imperial => crate::wttr::TempScale::Fahrenheit,
+            Units::Metric | Units::Both => crate::wttr::TempScale::Celsius,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, ValueEnum)]
+pub enum IconSet {
+    #[default]
+    Emoji,
+    /// Weather glyphs from a patched Nerd Font.
+    Nerdfont,
+    /// Two-character markers such as `:)` and `//`.
+    Ascii,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, ValueEnum)]
+pub enum ColorMode {
+    #[default]
+    Truecolor,
+    Ansi16,
+    /// The terminal's default colors, with no styling at all.
+    Monochrome,
+}
+
+impl ColorMode {
+    /// Uses the explicit choice if given, otherwise truecolor only when `COLORTERM` advertises it.
+    pub fn resolve(choice: Option<ColorMode>, colorterm: Option<&str>) -> ColorMode {
+        choice.unwrap_or(match colorterm {
+            Some("truecolor") | Some("24bit") => ColorMode::Truecolor,
+            _ => ColorMode::Ansi16,
+        })
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, ValueEnum)]
+pub enum ThemeName {
+    #[default]
+    Ceefax,
+    Amber,
+    Mono,
+}
+
+impl ThemeName {
+    pub fn theme(self) -> Theme {
+        match self {
+            ThemeName::Ceefax => CEEFAX_THEME,
+            ThemeName::Amber => AMBER_THEME,
+            ThemeName::Mono => MONO_THEME,
+        }
+    }
+}
+
+/// The colors drawn in place of the CEEFAX palette, which every page is written in terms of.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Theme {
+    pub blue: Color,
+    pub green: Color,
+    pub cyan: Color,
+    pub yellow: Color,
+    pub red: Color,
+    pub white: Color,
+    pub black: Color,
+    /// Hue that any other color, such as a temperature band, is shaded in by its brightness;
+    /// `None` leaves those colors as they are.
+    pub tint: Option<(u8, u8, u8)>,
+}
+
+pub const CEEFAX_THEME: Theme = Theme {
+    blue: CEEFAX_BLUE,
+    green: CEEFAX_GREEN,
+    cyan: CEEFAX_CYAN,
+    yellow: CEEFAX_YELLOW,
+    red: CEEFAX_RED,
+    white: CEEFAX_WHITE,
+    black: CEEFAX_BLACK,
+    tint: None,
+};
+
+pub const AMBER_THEME: Theme = Theme {
+    blue: Color::Rgb(40, 20, 0),
+    green: Color::Rgb(204, 136, 0),
+    cyan: Color::Rgb(255, 176, 0),
+    yellow: Color::Rgb(255, 204, 64),
+    red: Color::Rgb(255, 96, 0),
+    white: Color::Rgb(255, 176, 0),
+    black: Color::Rgb(0, 0, 0),
+    tint: Some((255, 176, 0)),
+};
+
+pub const MONO_THEME: Theme = Theme {
+    blue: Color::Rgb(0, 0, 0),
+    green: Color::Rgb(170, 170, 170),
+    cyan: Color::Rgb(255, 255, 255),
+    yellow: Color::Rgb(255, 255, 255),
+    red: Color::Rgb(170, 170, 170),
+    white: Color::Rgb(255, 255, 255),
+    black: Color::Rgb(0, 0, 0),
+    tint: Some((255, 255, 255)),
+};
+
+impl Default for Theme {
+    fn default() -> Self {
+        CEEFAX_THEME
+    }
+}
+
+impl Theme {
+    /// The theme's color for one the pages were drawn with.
+    pub fn recolor(&self, color: Color) -> Color {
+        match color {
+            CEEFAX_BLUE => self.blue,
+            CEEFAX_GREEN => self.green,
+            CEEFAX_CYAN => self.cyan,
+            CEEFAX_YELLOW => self.yellow,
+            CEEFAX_RED => self.red,
+            CEEFAX_WHITE => self.white,
+            CEEFAX_BLACK => self.black,
+            Color::Rgb(r, g, b) => match self.tint {
+                Some((tr, tg, tb)) => {
+                    // Rec. 601 luma, so yellows stay brighter than blues.
+                    let luma = (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000;
+                    let shade = |channel: u8| (channel as u32 * luma / 255) as u8;
+                    Color::Rgb(shade(tr), shade(tg), shade(tb))
+                }
+                None => color,
+            },
+            other => other,
+        }
+    }
+}
+
+/// Maps a palette color onto the closest of the 16 standard ANSI colors.
+pub fn to_ansi16(color: Color) -> Color {
+    const ANSI16: [(Color, (u8, u8, u8)); 16] = [
+        (Color::Black, (0, 0, 0)),
+        (Color::Red, (170, 0, 0)),
+        (Color::Green, (0, 170, 0)),
+        (Color::Yellow, (170, 170, 0)),
+        (Color::Blue, (0, 0, 170)),
+        (Color::Magenta, (170, 0, 170)),
+        (Color::Cyan, (0, 170, 170)),
+        (Color::Gray, (170, 170, 170)),
+        (Color::DarkGray, (85, 85, 85)),
+        (Color::LightRed, (255, 85, 85)),
+        (Color::LightGreen, (85, 255, 85)),
+        (Color::LightYellow, (255, 255, 85)),
+        (Color::LightBlue, (85, 85, 255)),
+        (Color::LightMagenta, (255, 85, 255)),
+        (Color::LightCyan, (85, 255, 255)),
+        (Color::White, (255, 255, 255)),
+    ];
+    match color {
+        Color::Rgb(r, g, b) => {
+            let distance = |(ar, ag, ab): (u8, u8, u8)| {
+                let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2);
+                d(r, ar) + d(g, ag) + d(b, ab)
+            };
+            ANSI16
+                .iter()
+                .min_by_key(|(_, rgb)| distance(*rgb))
+                .map_or(color, |(ansi, _)| *ansi)
+        }
+        other => other,
+    }
+}
+
+/// Network settings for the live clients, derived from the command line.
+#[derive(Clone)]
+pub struct FetchOptions {
+    /// Replaces any `HTTP(S)_PROXY` from the environment.
+    pub proxy: Option<String>,
+    pub retries: u32,
+    /// Limit on each whole request, from connecting to reading the body.
+    pub timeout: Duration,
+}
+
+impl FetchOptions {
+    /// The longest one request can take: every attempt timing out, plus the waits between them.
+    pub fn request_budget(&self) -> Duration {
+        let attempts = self.timeout.saturating_mul(self.retries.saturating_add(1));
+        let backoff = RETRY_BASE_DELAY.saturating_mul(2u32.saturating_pow(self.retries).saturating_sub(1));
+        attempts.saturating_add(backoff)
+    }
+}
+
+impl Default for FetchOptions {
+    fn default() -> Self {
+        Self {
+            proxy: None,
+            retries: 0,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+impl From<&Cli> for FetchOptions {
+    fn from(cli: &Cli) -> Self {
+        Self {
+            proxy: cli.proxy.clone(),
+            retries: cli.retries,
+            timeout: Duration::from_secs(cli.timeout_secs),
+        }
+    }
+}
+
+/// Rendering settings derived from the command line.
+#[derive(Clone)]
+pub struct DisplayOptions {
+    pub text_only: bool,
+    pub units: Units,
+    pub icons: IconSet,
+    pub color_mode: ColorMode,
+    pub theme: Theme,
+    pub temp_scheme: crate::wttr::TempScheme,
+    pub fit_map: bool,
+    pub map_glyphs: MapGlyphs,
+    pub overlay: Overlay,
+    pub outline_only: bool,
+    pub home_region: Option<String>,
+    pub uppercase_date: bool,
+}
+
+/// The settings the command line gives when no flags are passed.
+impl Default for DisplayOptions {
+    fn default() -> Self {
+        Self {
+            text_only: false,
+            units: Units::default(),
+            icons: IconSet::default(),
+            color_mode: ColorMode::default(),
+            theme: Theme::default(),
+            temp_scheme: Default::default(),
+            fit_map: false,
+            map_glyphs: MapGlyphs::default(),
+            overlay: Overlay::default(),
+            outline_only: false,
+            home_region: None,
+            uppercase_date: true,
+        }
+    }
+}
+
+impl From<&Cli> for DisplayOptions {
+    fn from(cli: &Cli) -> Self {
+        Self {
+            text_only: cli.text_only,
+            units: cli.units,
+            icons: cli.icons,
+            color_mode: if cli.no_color || env::var("NO_COLOR").is_ok_and(|value| !value.is_empty()) {
+                ColorMode::Monochrome
+            } else {
+                ColorMode::resolve(cli.color_mode, env::var("COLORTERM").ok().as_deref())
+            },
+            theme: cli.theme.theme(),
+            temp_scheme: Default::default(),
+            fit_map: cli.fit_map,
+            map_glyphs: cli.map_glyphs,
+            overlay: cli.overlay,
+            outline_only: cli.outline_only,
+            home_region: cli.home_region.clone(),
+            uppercase_date: !cli.no_uppercase_date,
+        }
+    }
+}
+
+impl Cli {
+    pub fn stale_threshold(&self) -> Duration {
+        self.stale_after.unwrap_or(self.refresh * 2)
+    }
+
+    /// Age below which a cached report is used; zero with `--force-refresh`, so every fetch goes out.
+    pub fn cache_ttl(&self) -> Duration {
+        if self.force_refresh {
+            return Duration::ZERO;
+        }
+        self.cache_ttl.unwrap_or(self.refresh)
+    }
+
+    /// How long a fetch may stay loading before the page shows an error.
+    pub fn loading_deadline(&self) -> Duration {
+        self.loading_timeout
+            .unwrap_or_else(|| FetchOptions::from(self).request_budget().max(MIN_LOADING_TIMEOUT))
+    }
+
+    /// Whether `--exit-after` was given and has elapsed.
+    pub fn exit_due(&self) -> bool {
+        self.exit_after.is_some_and(|limit| self.started.elapsed() >= limit)
+    }
+
+    /// Parses the CLI from `CEEFAX_WEATHER_ARGS` and the real argv,
+    /// so that flags given on the command line take precedence.
+    pub fn parse_with_env() -> Self {
+        Self::parse_from_env_and_argv(env::var(ARGS_ENV_VAR).ok().as_deref(), env::args())
+    }
+
+    /// Parses `argv` on top of `env_args`, the contents of `CEEFAX_WEATHER_ARGS`. An argument
+    /// given in `argv` replaces the same one from `env_args` and drops any there it conflicts
+    /// with, so `--serve` on the command line wins over `--stream` in the environment.
+    pub fn parse_from_env_and_argv(env_args: Option<&str>, argv: impl IntoIterator<Item = String>) -> Self {
+        let mut argv = argv.into_iter();
+        let program = argv.next().unwrap_or_default();
+        let argv: Vec<String> = argv.collect();
+        let command = Self::command();
+        let matches = |args: Vec<String>| {
+            let args = std::iter::once(program.clone()).chain(args);
+            command.clone().try_get_matches_from(args).unwrap_or_else(|e| e.exit())
+        };
+        let given = |matches: &clap::ArgMatches, arg: &clap::Arg| {
+            matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+        };
+        let from_argv = matches(argv.clone());
+        let from_env = matches(env_args.map(split_args).unwrap_or_default());
+        let conflict = |a: &clap::Arg, b: &clap::Arg| {
+            command.get_arg_conflicts_with(a).iter().any(|other| other.get_id() == b.get_id())
+        };
+
+        let mut args = vec![program.clone()];
+        for arg in command.get_arguments().filter(|arg| given(&from_env, arg)) {
+            let overridden = command
+                .get_arguments()
+                .filter(|other| given(&from_argv, other))
+                .any(|other| other.get_id() == arg.get_id() || conflict(arg, other) || conflict(other, arg));
+            if overridden {
+                continue;
+            }
+            let flag = format!("--{}", arg.get_long().expect("every option has a long name"));
+            if arg.get_action().takes_values() {
+                for value in from_env.get_raw(arg.get_id().as_str()).into_iter().flatten() {
+                    args.push(format!("{}={}", flag, value.to_string_lossy()));
+                }
+            } else {
+                args.push(flag);
+            }
+        }
+        args.extend(argv);
+        Self::parse_from(args)
+    }
+}
+
+/// The 16 map glyphs, indexed by the 2x2 land bitmask; teletext mosaics unless chosen otherwise.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MapGlyphs(pub [char; 16]);
+
+impl Default for MapGlyphs {
+    fn default() -> Self {
+        Self(TELETEXT_CHARS)
+    }
+}
+
+/// Parses a `--map-glyphs` preset name, or a custom set of exactly 16 characters.
+pub fn parse_map_glyphs(input: &str) -> Result<MapGlyphs, String> {
+    match input {
+        "mosaic" => Ok(MapGlyphs(TELETEXT_CHARS)),
+        "shade" => Ok(MapGlyphs(SHADE_CHARS)),
+        "ascii" => Ok(MapGlyphs(ASCII_CHARS)),
+        custom => {
+            let chars: Vec<char> = custom.chars().collect();
+            chars.try_into().map(MapGlyphs).map_err(|chars: Vec<char>| {
+                format!("expected mosaic, shade, ascii or 16 characters, got {} characters", chars.len())
+            })
+        }
+    }
+}
+
+/// Parses a positive duration with an `s`, `m` or `h` suffix; bare numbers are minutes.
+pub fn parse_duration(input: &str) -> Result<Duration, String> {
+    let input = input.trim();
+    let (number, unit_secs) = match input.char_indices().last() {
+        Some((i, 's')) => (&input[..i], 1),
+        Some((i, 'm')) => (&input[..i], 60),
+        Some((i, 'h')) => (&input[..i], 60 * 60),
+        _ => (input, 60),
+    };
+    let value: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}': expected e.g. 30s, 5m or 1h", input))?;
+    if value == 0 {
+        return Err("duration must be greater than zero".to_string());
+    }
+    let secs = value
+        .checked_mul(unit_secs)
+        .ok_or_else(|| format!("duration '{}' is too long", input))?;
+    Ok(Duration::from_secs(secs))
+}
+
+/// Parses `--refresh`, keeping it between `MIN_REFRESH` and `MAX_REFRESH` so the page neither
+/// hammers the weather service nor goes a day without updating.
+pub fn parse_refresh(input: &str) -> Result<Duration, String> {
+    let interval = parse_duration(input)?;
+    if !(MIN_REFRESH..=MAX_REFRESH).contains(&interval) {
+        return Err(format!(
+            "refresh interval must be between {}s and {}h",
+            MIN_REFRESH.as_secs(),
+            MAX_REFRESH.as_secs() / 3600
+        ));
+    }
+    Ok(interval)
+}
+
+/// Splits a string into arguments on whitespace, honouring single and double quotes.
+pub fn split_args(input: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<char> = None;
+
+    for c in input.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => current.push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                in_arg = true;
+            }
+            None if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            None => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if in_arg {
+        args.push(current);
+    }
+    args
 }
 
 // --- Map Configuration Structures ---
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Default, Deserialize)]
 pub struct Region {
     pub name: String,
     pub city: String,
     pub char: char,
-    pub temp_pos: [u16; 2],
+    /// Label position in template cells; computed from the region's centroid when omitted.
+    #[serde(default)]
+    pub temp_pos: Option<[u16; 2]>,
+    /// Further queries (a postcode, `lat,lon`, ...) tried in order if `city` returns no data.
+    #[serde(default)]
+    pub fallbacks: Vec<String>,
+    /// Extra cities sampled alongside `city` and combined according to `aggregate`.
+    #[serde(default)]
+    pub cities: Vec<String>,
+    #[serde(default)]
+    pub aggregate: Aggregation,
+    /// The region's name in the country's own language, shown beside `name` on the text pages.
+    #[serde(default)]
+    pub local_name: Option<String>,
+    /// Template columns spanned on the region's widest row; filled in by `fill_label_positions`.
+    #[serde(skip)]
+    pub width: usize,
+}
+
+/// How a region with extra `cities` combines their readings into the one shown on the map.
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Aggregation {
+    /// Show the city with the median current temperature.
+    #[default]
+    Representative,
+    /// Show the main city's conditions with temperatures averaged over every city.
+    Average,
+}
+
+impl Region {
+    /// The city followed by its fallback queries.
+    pub fn queries(&self) -> Vec<&str> {
+        std::iter::once(self.city.as_str())
+            .chain(self.fallbacks.iter().map(String::as_str))
+            .collect()
+    }
+
+    /// The name used in page headings, followed by the local name when the template gives one.
+    pub fn title(&self) -> String {
+        match &self.local_name {
+            Some(local) if *local != self.name => format!("{} / {}", self.name, local),
+            _ => self.name.clone(),
+        }
+    }
 }
 
 #[derive(Clone, Deserialize)]
 pub struct Country {
     pub map_template: Vec<String>,
     pub regions: Vec<Region>,
+    /// Index of the region described in the main page's left panel.
+    #[serde(default = "default_featured_left")]
+    pub featured_left: usize,
+    /// Index of the region shown in the footer, picture page and terminal title.
+    #[serde(default)]
+    pub featured_footer: usize,
+    /// Template rows or columns to drop from each edge before blank margins are trimmed.
+    #[serde(default)]
+    pub crop_top: usize,
+    #[serde(default)]
+    pub crop_bottom: usize,
+    #[serde(default)]
+    pub crop_left: usize,
+    #[serde(default)]
+    pub crop_right: usize,
+}
+
+fn default_featured_left() -> usize {
+    1
+}
+
+impl Country {
+    /// The left panel's region, falling back to the first region if the index is out of range.
+    pub fn left_region(&self) -> Option<&Region> {
+        self.regions.get(self.featured_left).or_else(|| self.regions.first())
+    }
+
+    /// The footer's region, falling back to the first region if the index is out of range.
+    pub fn footer_region(&self) -> Option<&Region> {
+        self.regions.get(self.featured_footer).or_else(|| self.regions.first())
+    }
 }
 
 // --- ASCII Art ---
@@ -52,37 +822,752 @@ pub const WEATHER_TITLE: &str = "
  ╚══╝╚══╝ ╚══════╝╚═╝  ╚═╝   ╚═╝   ╚═╝  ╚═╝╚══════╝╚═╝  ╚═╝
 ";
 
-/// Loads a country configuration from a TOML file.
+// --- Picture Page Graphics ---
+const BIG_SUN: &str = r"
+    \   |   /
+     .-'''-.
+ -- (       ) --
+     `-...-'
+    /   |   \
+";
+
+const BIG_MOON: &str = r"
+      .--.
+     /  .'
+    |  (
+     \  '.
+      '--'
+";
+
+const BIG_PARTLY_CLOUDY: &str = r"
+   \  /
+ _ /''.-.
+   \_(   ).
+   /(___(__)
+";
+
+const BIG_CLOUD: &str = r"
+      .--.
+   .-(    ).
+  (___.__)__)
+";
+
+const BIG_FOG: &str = r"
+ _ - _ - _ -
+  _ - _ - _
+ _ - _ - _ -
+";
+
+const BIG_RAIN: &str = r"
+      .-.
+     (   ).
+    (___(__)
+    ' ' ' '
+   ' ' ' '
+";
+
+const BIG_SNOW: &str = r"
+      .-.
+     (   ).
+    (___(__)
+     *  *  *
+    *  *  *
+";
+
+const BIG_THUNDER: &str = r"
+      .-.
+     (   ).
+    (___(__)
+     /_ /_
+      /  /
+";
+
+const BIG_UNKNOWN: &str = r"
+    .-.
+     __)
+    (
+     `-'
+     •
+";
+
+/// Returns the picture-page graphic for an emoji icon from `wttr::get_weather_icon`.
+pub fn big_icon_art(icon: &str) -> &'static str {
+    match icon {
+        "☀️" => BIG_SUN,
+        "🌙" => BIG_MOON,
+        "⛅" => BIG_PARTLY_CLOUDY,
+        "☁️" | "🌥️" => BIG_CLOUD,
+        "🌫️" => BIG_FOG,
+        "🌦️" | "🌧️" => BIG_RAIN,
+        "🌨️" | "❄️" => BIG_SNOW,
+        "🌩️" => BIG_THUNDER,
+        _ => BIG_UNKNOWN,
+    }
+}
+
+/// Short `--country` names for templates stored under a longer name.
+const COUNTRY_ALIASES: [(&str, &str); 2] = [("fr", "france"), ("us", "usa")];
+
+/// The template name for a `--country` argument, e.g. `france` for `FR`.
+pub fn canonical_country_name(name: &str) -> String {
+    let name = name.trim().to_lowercase();
+    COUNTRY_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map_or(name, |(_, canonical)| canonical.to_string())
+}
+
+/// Loads a country configuration from a TOML file, failing with the valid names if there is
+/// no template called `name`.
 pub fn load_country_config(name: &str) -> Result<Country, Box<dyn std::error::Error>> {
-    let mut exe_path = env::current_exe()?;
-    exe_path.pop();
-    let filename = exe_path.join("templates").join(format!("{}.toml", name));
-    
-    let config_str = fs::read_to_string(&filename)
+    let templates = templates_dir()?;
+    check_country_name(name, &countries_in(&templates)?)?;
+    Ok(load_country_file(&templates.join(format!("{}.toml", name)))?)
+}
+
+/// Checks that `name` is one of the `available` countries, suggesting the closest if it isn't.
+pub fn check_country_name(name: &str, available: &[String]) -> Result<(), String> {
+    if available.iter().any(|country| country == name) {
+        return Ok(());
+    }
+    // Allow roughly one typo per three letters.
+    let suggestion = available
+        .iter()
+        .map(|country| (edit_distance(name, country), country))
+        .filter(|(distance, country)| *distance <= (country.chars().count() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map_or(String::new(), |(_, country)| format!(" (did you mean '{}'?)", country));
+    Err(format!("no such country{}; choose from {}", suggestion, available.join(", ")))
+}
+
+/// The Levenshtein distance between two strings, counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Loads a country configuration from a TOML file at any path, e.g. one given with `--config`.
+pub fn load_country_file(filename: &Path) -> Result<Country, String> {
+    let config_str = fs::read_to_string(filename)
         .map_err(|e| format!("Failed to read config file at {:?}: {}", filename, e))?;
-    
-    let country: Country = toml::from_str(&config_str)
-        .map_err(|e| format!("Failed to parse TOML from {:?}: {}", filename, e))?;
-        
+
+    parse_country(&config_str).map_err(|e| format!("{} in {:?}", e, filename))
+}
+
+/// Parses and checks a country definition, then crops its map and places its labels.
+pub fn parse_country(config_str: &str) -> Result<Country, String> {
+    let mut country: Country = toml::from_str(config_str).map_err(|e| format!("Failed to parse TOML: {}", e))?;
+    validate_country(&country).map_err(|e| format!("Invalid country: {}", e))?;
+    crop_map(&mut country);
+    fill_label_positions(&mut country);
+
     Ok(country)
 }
 
-/// Scans the templates directory and returns a list of available country names.
-pub fn get_available_countries() -> io::Result<Vec<String>> {
-    let mut exe_path = env::current_exe()?;
-    exe_path.pop();
-    let templates_path = exe_path.join("templates");
-    
-    let mut countries = Vec::new();
-    for entry in fs::read_dir(templates_path)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.is_file() {
-            if let Some(stem) = path.file_stem().and_then(|s| s.to_str()) {
-                countries.push(stem.to_string());
+/// Checks that a country definition can be rendered unambiguously.
+pub fn validate_country(country: &Country) -> Result<(), String> {
+    for (i, region) in country.regions.iter().enumerate() {
+        if let Some(other) = country.regions[..i].iter().find(|other| other.char == region.char) {
+            return Err(format!(
+                "regions '{}' and '{}' both use map char '{}'",
+                other.name, region.name, region.char
+            ));
+        }
+        if !country.map_template.iter().any(|row| row.contains(region.char)) {
+            return Err(format!("region '{}' uses map char '{}', which the map template never draws", region.name, region.char));
+        }
+    }
+    Ok(())
+}
+
+/// Keeps only the regions named in `names` (case-insensitively) and returns the names that
+/// matched nothing. The country is left untouched when no name matches.
+pub fn retain_regions(country: &mut Country, names: &[String]) -> Vec<String> {
+    let wanted: Vec<&str> = names.iter().map(|name| name.trim()).filter(|name| !name.is_empty()).collect();
+    let unknown: Vec<String> = wanted
+        .iter()
+        .filter(|name| !country.regions.iter().any(|region| region.name.eq_ignore_ascii_case(name)))
+        .map(|name| name.to_string())
+        .collect();
+    if unknown.len() < wanted.len() {
+        let left = country.left_region().map(|region| region.name.clone());
+        let footer = country.footer_region().map(|region| region.name.clone());
+        country
+            .regions
+            .retain(|region| wanted.iter().any(|name| region.name.eq_ignore_ascii_case(name)));
+        // Keep featuring the same regions where they survived the filter.
+        let position = |name: Option<String>| country.regions.iter().position(|region| Some(&region.name) == name.as_ref());
+        country.featured_left = position(left).unwrap_or(1);
+        country.featured_footer = position(footer).unwrap_or(0);
+    }
+    unknown
+}
+
+/// Applies the country's explicit crop, then trims fully blank border rows and columns so the
+/// map is tightly framed. Explicit label positions are shifted to match.
+pub fn crop_map(country: &mut Country) {
+    let rows: Vec<Vec<char>> = country.map_template.iter().map(|row| row.chars().collect()).collect();
+    let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let bottom = rows.len().saturating_sub(country.crop_bottom);
+    let right = width.saturating_sub(country.crop_right);
+    let is_land = |x: usize, y: usize| rows[y].get(x).is_some_and(|c| *c != ' ');
+
+    let land_rows: Vec<usize> = (country.crop_top..bottom).filter(|&y| (country.crop_left..right).any(|x| is_land(x, y))).collect();
+    let land_cols: Vec<usize> = (country.crop_left..right).filter(|&x| land_rows.iter().any(|&y| is_land(x, y))).collect();
+    let (Some(&top), Some(&bottom), Some(&left), Some(&right)) = (land_rows.first(), land_rows.last(), land_cols.first(), land_cols.last()) else {
+        return;
+    };
+
+    country.map_template = rows[top..=bottom]
+        .iter()
+        .map(|row| (left..=right).map(|x| row.get(x).copied().unwrap_or(' ')).collect())
+        .collect();
+    for region in country.regions.iter_mut() {
+        if let Some([x, y]) = region.temp_pos {
+            region.temp_pos = Some([x.saturating_sub(left as u16), y.saturating_sub(top as u16)]);
+        }
+    }
+}
+
+/// Returns the centroid, in template cells, of every cell drawn with `region_char`.
+pub fn region_centroid(template: &[String], region_char: char) -> Option<[u16; 2]> {
+    let (mut sum_x, mut sum_y, mut count) = (0usize, 0usize, 0usize);
+    for (y, row) in template.iter().enumerate() {
+        for (x, c) in row.chars().enumerate() {
+            if c == region_char {
+                sum_x += x;
+                sum_y += y;
+                count += 1;
             }
         }
     }
+    if count == 0 {
+        return None;
+    }
+    let centre = |sum: usize| ((sum + count / 2) / count) as u16;
+    Some([centre(sum_x), centre(sum_y)])
+}
+
+/// Template columns spanned by `region_char` on its widest row, from its first cell to its last.
+pub fn region_width(template: &[String], region_char: char) -> usize {
+    template
+        .iter()
+        .filter_map(|row| {
+            let first = row.chars().position(|c| c == region_char)?;
+            let last = row.chars().count() - 1 - row.chars().rev().position(|c| c == region_char)?;
+            Some(last - first + 1)
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Gives every region without an explicit `temp_pos` a label at its centroid, and records
+/// each region's width so the map need not rescan the template every frame.
+pub fn fill_label_positions(country: &mut Country) {
+    for region in country.regions.iter_mut() {
+        if region.temp_pos.is_none() {
+            region.temp_pos = region_centroid(&country.map_template, region.char);
+        }
+        region.width = region_width(&country.map_template, region.char);
+    }
+}
+
+/// Scans the templates directory and returns the available country names in alphabetical order,
+/// so the numbered country list is the same on every run.
+pub fn get_available_countries() -> Result<Vec<String>, String> {
+    countries_in(&templates_dir()?)
+}
+
+/// Where the country last chosen from the menu is remembered:
+/// `$XDG_CONFIG_HOME/ceefax-weather/last-country`, falling back to `~/.config/ceefax-weather`.
+pub fn last_country_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("ceefax-weather").join("last-country"))
+}
+
+/// The country name stored at `path`, if there is one.
+pub fn load_last_country(path: &Path) -> Option<String> {
+    let name = fs::read_to_string(path).ok()?;
+    let name = name.trim();
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+pub fn store_last_country(path: &Path, name: &str) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, format!("{}\n", name))
+}
+
+/// The `templates` directory installed next to the executable.
+fn templates_dir() -> Result<PathBuf, String> {
+    let mut exe_path = env::current_exe().map_err(|e| format!("Failed to locate the executable: {}", e))?;
+    exe_path.pop();
+    Ok(exe_path.join("templates"))
+}
+
+/// The names of the `.toml` country definitions in `dir`, sorted.
+fn countries_in(dir: &Path) -> Result<Vec<String>, String> {
+    let entries = fs::read_dir(dir).map_err(|e| format!("Failed to read templates directory {:?}: {}", dir, e))?;
+    let mut countries: Vec<String> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.extension().is_some_and(|ext| ext == "toml"))
+        .filter_map(|path| path.file_stem().and_then(|s| s.to_str()).map(str::to_string))
+        .collect();
+    countries.sort();
     Ok(countries)
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_args_respects_quotes() {
+        assert_eq!(split_args("--country uk"), vec!["--country", "uk"]);
+        assert_eq!(
+            split_args(r#"  --country "new zealand"  -c 'a b' "" "#),
+            vec!["--country", "new zealand", "-c", "a b", ""]
+        );
+        assert!(split_args("   ").is_empty());
+    }
+
+    #[test]
+    fn test_argv_overrides_env_args() {
+        let parse = |env_args: &str, argv: &[&str]| {
+            let argv = std::iter::once("ceefax-weather").chain(argv.iter().copied()).map(String::from);
+            Cli::parse_from_env_and_argv(Some(env_args), argv)
+        };
+
+        let cli = parse("--country germany --units imperial --regions Wales", &["--country", "berlin", "--regions", "Scotland"]);
+        assert_eq!(cli.country.as_deref(), Some("berlin"));
+        assert_eq!(cli.units, Units::Imperial);
+        assert_eq!(cli.regions, vec!["Scotland"]);
+
+        // A command-line flag also drops the environment flags it can't be combined with.
+        let cli = parse("--stream --force-refresh", &["--serve", "8080", "--background-refresh"]);
+        assert_eq!((cli.stream, cli.serve, cli.force_refresh, cli.background_refresh), (false, Some(8080), false, true));
+        let cli = parse("--stream --home-region 'S. England'", &[]);
+        assert!(cli.stream);
+        assert_eq!(cli.home_region.as_deref(), Some("S. England"));
+    }
+
+    #[test]
+    fn test_label_position_defaults_to_centroid() {
+        let mut country: Country = toml::from_str(
+            r#"
+            map_template = [
+                "AAAA    ",
+                "AAAA  BB",
+                "AAAA  BB",
+            ]
+
+            [[regions]]
+            name = "A"
+            city = "A"
+            char = 'A'
+
+            [[regions]]
+            name = "B"
+            city = "B"
+            char = 'B'
+            temp_pos = [0, 0]
+            "#,
+        )
+        .unwrap();
+        fill_label_positions(&mut country);
+
+        assert_eq!(country.regions[0].temp_pos, Some([2, 1]));
+        assert_eq!(country.regions[1].temp_pos, Some([0, 0]));
+        assert_eq!(region_centroid(&country.map_template, 'B'), Some([7, 2]));
+        assert_eq!(region_centroid(&country.map_template, 'Z'), None);
+    }
+
+    #[test]
+    fn test_map_glyph_presets_and_custom_sets() {
+        assert_eq!(parse_map_glyphs("mosaic"), Ok(MapGlyphs::default()));
+        assert_eq!(parse_map_glyphs("ascii").unwrap().0[15], '#');
+        assert_eq!(parse_map_glyphs(" .:#.:#|.:#|:#|█").unwrap().0[15], '█');
+        assert!(parse_map_glyphs("#+.").is_err());
+        // Options built without the command line still draw teletext mosaics, not NULs.
+        assert_eq!(DisplayOptions::default().map_glyphs.0, TELETEXT_CHARS);
+    }
+
+    #[test]
+    fn test_default_display_options_match_the_cli_defaults() {
+        let default = DisplayOptions::default();
+        let cli = DisplayOptions::from(&Cli::parse_from(["ceefax-weather"]));
+        assert!(default.uppercase_date && cli.uppercase_date);
+        assert_eq!((default.units, default.icons, default.overlay), (cli.units, cli.icons, cli.overlay));
+        assert_eq!((default.theme, default.map_glyphs), (cli.theme, cli.map_glyphs));
+        assert_eq!((default.fit_map, default.outline_only, default.text_only), (cli.fit_map, cli.outline_only, cli.text_only));
+    }
+
+    #[test]
+    fn test_color_mode_detection() {
+        assert_eq!(ColorMode::resolve(None, Some("truecolor")), ColorMode::Truecolor);
+        assert_eq!(ColorMode::resolve(None, Some("24bit")), ColorMode::Truecolor);
+        assert_eq!(ColorMode::resolve(None, None), ColorMode::Ansi16);
+        assert_eq!(ColorMode::resolve(Some(ColorMode::Truecolor), None), ColorMode::Truecolor);
+        assert_eq!(ColorMode::resolve(Some(ColorMode::Ansi16), Some("truecolor")), ColorMode::Ansi16);
+    }
+
+    #[test]
+    fn test_themes_recolor_the_palette() {
+        let themes: Vec<Theme> = ThemeName::value_variants().iter().map(|name| name.theme()).collect();
+        for (i, theme) in themes.iter().enumerate() {
+            // The blue page background is what sets each theme apart.
+            assert_eq!(theme.recolor(CEEFAX_BLUE), theme.blue);
+            assert!(themes[..i].iter().all(|other| other.blue != theme.blue));
+        }
+        assert_eq!(CEEFAX_THEME.recolor(SUNSET_ORANGE), SUNSET_ORANGE);
+        assert_eq!(AMBER_THEME.recolor(CEEFAX_WHITE), Color::Rgb(255, 176, 0));
+        assert_eq!(MONO_THEME.recolor(NO_DATA_GREY), NO_DATA_GREY);
+        assert_eq!(AMBER_THEME.recolor(Color::Reset), Color::Reset);
+    }
+
+    #[test]
+    fn test_palette_maps_to_ansi16() {
+        assert_eq!(to_ansi16(CEEFAX_BLUE), Color::Blue);
+        assert_eq!(to_ansi16(CEEFAX_GREEN), Color::Green);
+        assert_eq!(to_ansi16(CEEFAX_CYAN), Color::Cyan);
+        assert_eq!(to_ansi16(CEEFAX_YELLOW), Color::Yellow);
+        assert_eq!(to_ansi16(CEEFAX_RED), Color::Red);
+        assert_eq!(to_ansi16(CEEFAX_WHITE), Color::White);
+        assert_eq!(to_ansi16(CEEFAX_BLACK), Color::Black);
+        assert_eq!(to_ansi16(Color::Reset), Color::Reset);
+    }
+
+    #[test]
+    fn test_parse_duration_units() {
+        assert_eq!(parse_duration("30s"), Ok(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Ok(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+        assert_eq!(parse_duration("15"), Ok(Duration::from_secs(900)));
+    }
+
+    #[test]
+    fn test_parse_duration_rejects_invalid() {
+        assert!(parse_duration("0s").is_err());
+        assert!(parse_duration("-5m").is_err());
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("m").is_err());
+        assert!(parse_duration("10d").is_err());
+        assert!(parse_duration("1.5h").is_err());
+        assert_eq!(parse_duration("999999999999999999h").unwrap_err(), "duration '999999999999999999h' is too long");
+        assert!(parse_duration("18446744073709551615s").is_ok());
+    }
+
+    #[test]
+    fn test_refresh_interval_is_bounded() {
+        assert_eq!(Cli::parse_from(["ceefax-weather"]).refresh, Duration::from_secs(15 * 60));
+        assert_eq!(Cli::parse_from(["ceefax-weather", "--refresh-mins", "5"]).refresh, Duration::from_secs(5 * 60));
+        assert_eq!(Cli::parse_from(["ceefax-weather", "--refresh", "1h"]).refresh, Duration::from_secs(60 * 60));
+        assert!(Cli::try_parse_from(["ceefax-weather", "--refresh-mins", "0"]).is_err());
+        assert!(Cli::try_parse_from(["ceefax-weather", "--refresh", "5s"]).is_err());
+        assert!(Cli::try_parse_from(["ceefax-weather", "--refresh-mins", "100000"]).is_err());
+        assert!(Cli::try_parse_from(["ceefax-weather", "--refresh-mins", "999999999999999999"]).is_err());
+        assert!(parse_refresh("30s").is_ok() && parse_refresh("24h").is_ok());
+        assert_eq!(parse_refresh("25h").unwrap_err(), "refresh interval must be between 30s and 24h");
+    }
+
+    #[test]
+    fn test_stale_threshold_defaults_to_twice_refresh() {
+        let cli = Cli::parse_from(["ceefax-weather", "--refresh", "10m"]);
+        assert_eq!(cli.stale_threshold(), Duration::from_secs(20 * 60));
+        let cli = Cli::parse_from(["ceefax-weather", "--stale-after", "1h"]);
+        assert_eq!(cli.stale_threshold(), Duration::from_secs(60 * 60));
+    }
+
+    #[test]
+    fn test_loading_deadline_covers_every_retry() {
+        assert_eq!(Cli::parse_from(["ceefax-weather"]).loading_deadline(), MIN_LOADING_TIMEOUT);
+        // Four 30s attempts with 200ms, 400ms and 800ms between them.
+        let cli = Cli::parse_from(["ceefax-weather", "--timeout-secs", "30"]);
+        assert_eq!(cli.loading_deadline(), Duration::from_millis(121_400));
+        let cli = Cli::parse_from(["ceefax-weather", "--timeout-secs", "30", "--loading-timeout", "5m"]);
+        assert_eq!(cli.loading_deadline(), Duration::from_secs(5 * 60));
+        let cli = Cli::parse_from(["ceefax-weather", "--timeout-secs", "18446744073709551615", "--retries", "4000000000"]);
+        assert_eq!(cli.loading_deadline(), Duration::MAX);
+    }
+
+    #[test]
+    fn test_exit_after_elapses() {
+        let mut cli = Cli::parse_from(["ceefax-weather"]);
+        assert!(!cli.exit_due());
+        cli = Cli::parse_from(["ceefax-weather", "--exit-after", "30s"]);
+        assert!(!cli.exit_due());
+        cli.started -= Duration::from_secs(31);
+        assert!(cli.exit_due());
+    }
+
+    #[test]
+    fn test_featured_regions_default_and_fall_back() {
+        let country: Country = toml::from_str(
+            r#"
+            map_template = ["S"]
+
+            [[regions]]
+            name = "Scotland"
+            city = "Edinburgh"
+            char = 'S'
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!((country.featured_left, country.featured_footer), (1, 0));
+        // Only one region, so the left panel falls back to it.
+        assert_eq!(country.left_region().unwrap().name, "Scotland");
+        assert_eq!(country.footer_region().unwrap().name, "Scotland");
+    }
+
+    #[test]
+    fn test_blank_map_margins_are_trimmed() {
+        let mut country: Country = toml::from_str(
+            r#"
+            map_template = [
+                "        ",
+                "   AA   ",
+                "  AAB   ",
+                "        ",
+                "        ",
+            ]
+
+            [[regions]]
+            name = "A"
+            city = "A"
+            char = 'A'
+            temp_pos = [3, 2]
+
+            [[regions]]
+            name = "B"
+            city = "B"
+            char = 'B'
+            "#,
+        )
+        .unwrap();
+
+        crop_map(&mut country);
+        assert_eq!(country.map_template, vec![" AA", "AAB"]);
+        assert_eq!(country.regions[0].temp_pos, Some([1, 1]));
+
+        // An explicit crop is applied before the blank margins are trimmed.
+        country.crop_right = 1;
+        crop_map(&mut country);
+        assert_eq!(country.map_template, vec![" A", "AA"]);
+    }
+
+    #[test]
+    fn test_duplicate_region_chars_are_rejected() {
+        let country: Country = toml::from_str(
+            r#"
+            map_template = ["EEEE"]
+
+            [[regions]]
+            name = "East"
+            city = "Berlin"
+            char = 'E'
+
+            [[regions]]
+            name = "England"
+            city = "London"
+            char = 'E'
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            validate_country(&country).unwrap_err(),
+            "regions 'East' and 'England' both use map char 'E'"
+        );
+    }
+
+    #[test]
+    fn test_region_chars_must_appear_in_the_template() {
+        let country: Country = toml::from_str(
+            r#"
+            map_template = ["NNSS"]
+
+            [[regions]]
+            name = "North"
+            city = "Leeds"
+            char = 'N'
+
+            [[regions]]
+            name = "West"
+            city = "Bristol"
+            char = 'W'
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            validate_country(&country).unwrap_err(),
+            "region 'West' uses map char 'W', which the map template never draws"
+        );
+    }
+
+    #[test]
+    fn test_country_loads_from_a_toml_file() {
+        let path = env::temp_dir().join(format!("ceefax-weather-country-{}.toml", std::process::id()));
+        fs::write(
+            &path,
+            r#"
+            map_template = ["  NN  ", " SSSS "]
+
+            [[regions]]
+            name = "North"
+            city = "Inverness"
+            char = 'N'
+
+            [[regions]]
+            name = "South"
+            city = "Glasgow"
+            char = 'S'
+            temp_pos = [2, 1]
+            "#,
+        )
+        .unwrap();
+        let country = load_country_file(&path);
+        fs::remove_file(&path).unwrap();
+
+        let country = country.unwrap();
+        assert_eq!(country.map_template, vec![" NN ", "SSSS"]);
+        assert_eq!(country.regions[0].city, "Inverness");
+        assert_eq!(country.regions[0].temp_pos, Some([2, 0]));
+        assert_eq!(country.regions[1].temp_pos, Some([1, 1]));
+        assert!(load_country_file(&path).is_err_and(|e| e.starts_with("Failed to read config file")));
+    }
+
+    #[test]
+    fn test_available_countries_are_sorted_and_include_the_built_ins() {
+        let countries = countries_in(&Path::new(env!("CARGO_MANIFEST_DIR")).join("templates")).unwrap();
+        assert!(countries.contains(&"uk".to_string()));
+        assert!(countries.contains(&"germany".to_string()));
+        assert!(countries.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(countries_in(Path::new("/nonexistent/templates")).is_err());
+    }
+
+    #[test]
+    fn test_every_bundled_template_is_valid() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates");
+        for name in countries_in(&dir).unwrap() {
+            let country = load_country_file(&dir.join(format!("{}.toml", name))).unwrap();
+            let width = country.map_template.iter().map(|row| row.chars().count()).max().unwrap();
+            for region in &country.regions {
+                let [x, y] = region.temp_pos.unwrap();
+                assert!((x as usize) < width && (y as usize) < country.map_template.len(), "{} label is off the {} map", region.name, name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_local_names_are_shown_beside_the_region_name() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("templates");
+        let greece = load_country_file(&dir.join("greece.toml")).unwrap();
+        assert_eq!(greece.regions[0].title(), "Attiki / Αττική");
+        let portugal = load_country_file(&dir.join("portugal.toml")).unwrap();
+        let titles: Vec<String> = portugal.regions.iter().map(Region::title).collect();
+        assert_eq!(titles, ["Centre & Lisbon / Centro & Lisboa", "North / Norte", "Algarve"]);
+    }
+
+    #[test]
+    fn test_unknown_countries_are_rejected_with_a_suggestion() {
+        let available: Vec<String> = ["france", "germany", "uk"].iter().map(|name| name.to_string()).collect();
+        assert!(check_country_name("germany", &available).is_ok());
+        assert_eq!(
+            check_country_name("germny", &available).unwrap_err(),
+            "no such country (did you mean 'germany'?); choose from france, germany, uk"
+        );
+        assert_eq!(check_country_name("atlantis", &available).unwrap_err(), "no such country; choose from france, germany, uk");
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_last_country_round_trip() {
+        let path = env::temp_dir()
+            .join(format!("ceefax-weather-state-{}", std::process::id()))
+            .join("last-country");
+        assert_eq!(load_last_country(&path), None);
+        store_last_country(&path, "germany").unwrap();
+        assert_eq!(load_last_country(&path).as_deref(), Some("germany"));
+        store_last_country(&path, "").unwrap();
+        assert_eq!(load_last_country(&path), None);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_country_aliases() {
+        assert_eq!(canonical_country_name("fr"), "france");
+        assert_eq!(canonical_country_name("FR"), "france");
+        assert_eq!(canonical_country_name("France"), "france");
+        assert_eq!(canonical_country_name("us"), "usa");
+        assert_eq!(canonical_country_name("uk"), "uk");
+    }
+
+    #[test]
+    fn test_retain_regions_filters_and_reports_unknown_names() {
+        let region = |name: &str, char| Region {
+            name: name.to_string(),
+            city: name.to_string(),
+            char,
+            ..Default::default()
+        };
+        let mut country = Country {
+            map_template: vec!["SWE".to_string()],
+            regions: vec![region("Scotland", 'S'), region("Wales", 'W'), region("England", 'E')],
+            featured_left: 2,
+            featured_footer: 0,
+            crop_top: 0,
+            crop_bottom: 0,
+            crop_left: 0,
+            crop_right: 0,
+        };
+
+        assert!(retain_regions(&mut country, &[]).is_empty());
+        assert_eq!(country.regions.len(), 3);
+
+        let unknown = retain_regions(&mut country, &["wales".to_string(), " Scotland ".to_string(), "Cornwall".to_string()]);
+        assert_eq!(unknown, vec!["Cornwall".to_string()]);
+        let names: Vec<&str> = country.regions.iter().map(|region| region.name.as_str()).collect();
+        assert_eq!(names, vec!["Scotland", "Wales"]);
+        // England was featured on the left but was filtered out.
+        assert_eq!(country.left_region().unwrap().name, "Wales");
+        assert_eq!(country.footer_region().unwrap().name, "Scotland");
+
+        assert_eq!(retain_regions(&mut country, &["Bavaria".to_string()]), vec!["Bavaria".to_string()]);
+        assert_eq!(country.regions.len(), 2);
+    }
+
+    #[test]
+    fn test_start_view_is_validated() {
+        let cli = Cli::parse_from(["ceefax-weather", "--start-view", "details"]);
+        assert_eq!(cli.start_view, StartView::Details);
+        let cli = Cli::parse_from(["ceefax-weather", "--start-view", "ranking"]);
+        assert_eq!(cli.start_view, StartView::Ranking);
+        let cli = Cli::parse_from(["ceefax-weather", "--start-view", "outlook"]);
+        assert_eq!(cli.start_view, StartView::Forecast);
+        assert!(Cli::try_parse_from(["ceefax-weather", "--start-view", "radar"]).is_err());
+    }
+
+    #[test]
+    fn test_metrics_listen_locally_by_default() {
+        assert!(Cli::parse_from(["ceefax-weather"]).metrics_address.is_loopback());
+        let cli = Cli::parse_from(["ceefax-weather", "--metrics-address", "0.0.0.0"]);
+        assert!(cli.metrics_address.is_unspecified());
+        assert!(Cli::try_parse_from(["ceefax-weather", "--metrics-address", "localhost:80"]).is_err());
+    }
+}
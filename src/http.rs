@@ -0,0 +1,66 @@
+use std::{
+    io::{self, Read, Write},
+    net::{IpAddr, TcpListener},
+    thread,
+    time::Duration,
+};
+
+/// How long a client may take to send its request or read the response.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Binds `address:port` and answers every request with `body()` on background threads,
+/// whatever the method or path.
+pub fn serve(
+    address: IpAddr,
+    port: u16,
+    content_type: &'static str,
+    body: impl Fn() -> String + Send + Sync + 'static,
+) -> io::Result<()> {
+    let listener = TcpListener::bind((address, port))?;
+    let body = std::sync::Arc::new(body);
+    thread::spawn(move || {
+        for mut stream in listener.incoming().flatten() {
+            let body = body.clone();
+            // One slow or silent client mustn't hold up the next.
+            thread::spawn(move || {
+                let _ = stream.set_read_timeout(Some(CLIENT_TIMEOUT));
+                let _ = stream.set_write_timeout(Some(CLIENT_TIMEOUT));
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request);
+                let _ = stream.write_all(response(content_type, &body()).as_bytes());
+            });
+        }
+    });
+    Ok(())
+}
+
+fn response(content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        content_type,
+        body.len(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::{Ipv4Addr, TcpStream};
+
+    #[test]
+    fn test_serves_body_to_each_connection() {
+        // Find a free port, then hand it to the server.
+        let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap().local_addr().unwrap().port();
+        serve(Ipv4Addr::LOCALHOST.into(), port, "text/plain", || "hello".to_string()).unwrap();
+
+        // A client that never sends a request doesn't block the next one.
+        let _idle = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, port)).unwrap();
+        stream.write_all(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).unwrap();
+        assert!(reply.starts_with("HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nContent-Length: 5\r\n"));
+        assert!(reply.ends_with("\r\n\r\nhello"));
+    }
+}
@@ -1,56 +1,216 @@
 mod app;
+#[cfg(feature = "async")]
+mod async_client;
+mod cache;
 mod config;
+mod http;
+mod metrics;
+mod open_meteo;
+mod preview;
 mod ui;
 mod wttr;
 
-use clap::Parser;
 use crossterm::{
+    cursor::Show,
+    style::Print,
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
 use ratatui::backend::CrosstermBackend;
-use std::{io, sync::Arc};
+use std::{
+    io,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// xterm sequences that save and restore the window title.
+const PUSH_TITLE: &str = "\x1b[22;0t";
+const POP_TITLE: &str = "\x1b[23;0t";
+
+/// Whether the title was saved with `PUSH_TITLE` and not yet put back.
+static TITLE_PUSHED: AtomicBool = AtomicBool::new(false);
+
+/// Restores the terminal when dropped, so an early return on error leaves it usable too.
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = restore_terminal();
+    }
+}
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let cli = config::Cli::parse();
-    let mut current_country_name = cli.country;
+    let mut cli = config::Cli::parse_with_env();
+    // Without `--country`, start where the country menu last left off, if that country still exists.
+    let last_country_path = config::last_country_path();
+    let requested_country = cli
+        .country
+        .clone()
+        .or_else(|| {
+            let last = config::load_last_country(last_country_path.as_deref()?)?;
+            config::get_available_countries().ok()?.contains(&last).then_some(last)
+        })
+        .unwrap_or_else(|| "uk".to_string());
+    // A `--config` country is named after its file, e.g. `alps` for `alps.toml`.
+    let mut current_country_name = match &cli.config {
+        Some(path) => path.file_stem().map_or(config::canonical_country_name(&requested_country), |stem| stem.to_string_lossy().into_owned()),
+        None => config::canonical_country_name(&requested_country),
+    };
+    let mut chosen_country = None;
+
+    let metrics = Arc::new(metrics::Metrics::default());
+    if let Some(port) = cli.metrics_port {
+        if let Err(e) = metrics::serve(cli.metrics_address, port, metrics.clone()) {
+            eprintln!("Failed to start metrics server on port {}: {}", port, e);
+            std::process::exit(1);
+        }
+    }
+
+    // Restore the terminal before the panic message is printed so it stays readable.
+    // Panics on worker threads don't end the TUI, so only the main thread restores.
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if std::thread::current().name() == Some("main") {
+            let _ = restore_terminal();
+        }
+        default_hook(info);
+    }));
+
+    let disk_cache = cache::DiskCache::default_dir()
+        .filter(|_| !cli.no_cache)
+        .map(|dir| cache::DiskCache::new(dir, cli.cache_ttl()));
+    // Create the single, shareable client for the application's lifetime.
+    let client = build_client(&cli, disk_cache).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+
+    // Load the first country up front so problems are reported before the TUI starts.
+    let (first_country, unknown_regions) = load_country(&current_country_name, cli.config.as_deref(), &cli.regions);
+    if !unknown_regions.is_empty() {
+        eprintln!(
+            "Error: '{}' has no region named {}",
+            current_country_name,
+            unknown_regions.iter().map(|name| format!("'{}'", name)).collect::<Vec<_>>().join(", ")
+        );
+        std::process::exit(1);
+    }
+
+    if cli.stream {
+        app::run_stream(&current_country_name, first_country, client, &cli, &metrics, &mut io::stdout().lock())?;
+        return Ok(());
+    }
+
+    if let Some(port) = cli.serve {
+        let preview = Arc::new(preview::Preview::default());
+        if let Err(e) = preview::serve(cli.serve_address, port, preview.clone()) {
+            eprintln!("Failed to start preview server on port {}: {}", port, e);
+            std::process::exit(1);
+        }
+        eprintln!("Serving the {} weather page on http://localhost:{}/", current_country_name, port);
+        app::run_headless(&current_country_name, first_country, client, &cli, &metrics, &preview)?;
+        return Ok(());
+    }
 
     enable_raw_mode()?;
+    let guard = TerminalGuard;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    if !cli.no_title_update {
+        // Save the current title on the terminal's title stack so it can be put back on exit.
+        execute!(stdout, Print(PUSH_TITLE))?;
+        TITLE_PUSHED.store(true, Ordering::SeqCst);
+    }
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = ratatui::Terminal::new(backend)?;
 
-    // Create the single, shareable client for the application's lifetime.
-    let client = Arc::new(wttr::LiveWeatherClient::new());
-
-    loop {
-        let country_config = config::load_country_config(&current_country_name).unwrap_or_else(|e| {
-            eprintln!(
-                "Error loading configuration for '{}': {}",
-                current_country_name, e
-            );
-            std::process::exit(1);
-        });
+    let mut country_cache = app::CountryCache::new();
+
+    if cli.colortest {
+        app::run_colortest(&mut terminal)?;
+    } else {
+        let mut first_country = Some(first_country);
+        loop {
+            let country_config = first_country
+                .take()
+                .unwrap_or_else(|| load_country(&current_country_name, None, &cli.regions).0);
 
-        // Inject the client into the application loop.
-        match app::run_app(&mut terminal, country_config, client.clone())? {
-            Some(new_country) => {
-                current_country_name = new_country;
+            // Inject the client into the application loop.
+            match app::run_app(
+                &mut terminal,
+                &current_country_name,
+                country_config,
+                client.clone(),
+                &cli,
+                &mut country_cache,
+                &metrics,
+            )? {
+                Some(new_country) => {
+                    chosen_country = Some(new_country.clone());
+                    current_country_name = new_country;
+                    // The start view only applies to the first page shown after launch.
+                    cli.start_view = config::StartView::Main;
+                }
+                None => break,
             }
-            None => break,
         }
     }
 
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    drop(guard);
 
+    if let (Some(path), Some(name)) = (&last_country_path, &chosen_country) {
+        if let Err(e) = config::store_last_country(path, name) {
+            eprintln!("Warning: couldn't remember '{}' as the last country in {:?}: {}", name, path, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// The weather client for the chosen provider.
+fn build_client(cli: &config::Cli, disk_cache: Option<cache::DiskCache>) -> Result<Arc<dyn wttr::WeatherClient>, String> {
+    let options = config::FetchOptions::from(cli);
+    #[cfg(feature = "async")]
+    if cli.concurrent_fetch {
+        if cli.provider != config::Provider::Wttr {
+            return Err("Error: --concurrent-fetch only works with --provider wttr".to_string());
+        }
+        return Ok(Arc::new(async_client::AsyncLiveWeatherClient::new(disk_cache, &options)?));
+    }
+    if cli.provider == config::Provider::OpenMeteo {
+        return Ok(Arc::new(open_meteo::OpenMeteoClient::new(disk_cache, &options)?));
+    }
+    Ok(Arc::new(wttr::LiveWeatherClient::new(disk_cache, &options)?))
+}
+
+/// Loads a country, from `path` if given or else by name from `templates/`, exiting on error,
+/// and applies the `--regions` filter.
+/// Returns the country together with any filter names it has no region for.
+fn load_country(name: &str, path: Option<&Path>, regions: &[String]) -> (config::Country, Vec<String>) {
+    let loaded = match path {
+        Some(path) => config::load_country_file(path).map_err(Into::into),
+        None => config::load_country_config(name),
+    };
+    let mut country = loaded.unwrap_or_else(|e| {
+        let _ = restore_terminal();
+        eprintln!("Error loading configuration for '{}': {}", name, e);
+        std::process::exit(1);
+    });
+    let unknown = config::retain_regions(&mut country, regions);
+    (country, unknown)
+}
+
+/// Leaves raw mode and the alternate screen, shows the cursor again and puts back the saved title.
+fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture, Show)?;
+    if TITLE_PUSHED.swap(false, Ordering::SeqCst) {
+        execute!(io::stdout(), Print(POP_TITLE))?;
+    }
     Ok(())
 }
 
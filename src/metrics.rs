@@ -0,0 +1,106 @@
+use crate::{app::AppData, http};
+use std::{
+    collections::BTreeMap,
+    io,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+};
+
+#[derive(Default)]
+struct MetricsState {
+    fetch_success: u64,
+    fetch_failure: u64,
+    last_fetch_timestamp: Option<i64>,
+    country: String,
+    temperatures: BTreeMap<String, f64>,
+}
+
+/// Fetch counters and current temperatures, shared between the TUI and the metrics server.
+#[derive(Default)]
+pub struct Metrics {
+    state: Mutex<MetricsState>,
+}
+
+impl Metrics {
+    pub fn record_success(&self, country: &str, data: &AppData) {
+        let mut state = self.state.lock().unwrap();
+        state.fetch_success += 1;
+        state.last_fetch_timestamp = Some(chrono::Utc::now().timestamp());
+        state.country = country.to_string();
+        state.temperatures = data
+            .reports
+            .iter()
+            .filter_map(|(region, report)| {
+                let temp = report.current_condition.first()?.temp_C.parse::<f64>().ok()?;
+                Some((region.clone(), temp))
+            })
+            .collect();
+    }
+
+    pub fn record_failure(&self) {
+        self.state.lock().unwrap().fetch_failure += 1;
+    }
+
+    /// Renders the current values in the Prometheus text exposition format.
+    pub fn render(&self) -> String {
+        let state = self.state.lock().unwrap();
+        let mut out = String::new();
+        out.push_str("# HELP ceefax_weather_fetch_success_total Completed weather fetches.\n");
+        out.push_str("# TYPE ceefax_weather_fetch_success_total counter\n");
+        out.push_str(&format!("ceefax_weather_fetch_success_total {}\n", state.fetch_success));
+        out.push_str("# HELP ceefax_weather_fetch_failure_total Failed weather fetches.\n");
+        out.push_str("# TYPE ceefax_weather_fetch_failure_total counter\n");
+        out.push_str(&format!("ceefax_weather_fetch_failure_total {}\n", state.fetch_failure));
+        if let Some(timestamp) = state.last_fetch_timestamp {
+            out.push_str("# HELP ceefax_weather_last_fetch_timestamp_seconds Unix time of the last successful fetch.\n");
+            out.push_str("# TYPE ceefax_weather_last_fetch_timestamp_seconds gauge\n");
+            out.push_str(&format!("ceefax_weather_last_fetch_timestamp_seconds {}\n", timestamp));
+        }
+        if !state.temperatures.is_empty() {
+            out.push_str("# HELP ceefax_weather_temperature_celsius Current temperature per region.\n");
+            out.push_str("# TYPE ceefax_weather_temperature_celsius gauge\n");
+            for (region, temp) in &state.temperatures {
+                out.push_str(&format!(
+                    "ceefax_weather_temperature_celsius{{country=\"{}\",region=\"{}\"}} {}\n",
+                    escape_label(&state.country),
+                    escape_label(region),
+                    temp
+                ));
+            }
+        }
+        out
+    }
+}
+
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+/// Binds the metrics port and serves every request with the current metrics.
+pub fn serve(address: IpAddr, port: u16, metrics: Arc<Metrics>) -> io::Result<()> {
+    http::serve(address, port, "text/plain; version=0.0.4", move || metrics.render())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prometheus_text() {
+        let metrics = Metrics::default();
+        metrics.record_failure();
+        {
+            let mut state = metrics.state.lock().unwrap();
+            state.country = "uk".to_string();
+            state.temperatures.insert("N. \"Ireland\"".to_string(), 12.5);
+        }
+
+        let text = metrics.render();
+        assert!(text.contains("ceefax_weather_fetch_success_total 0\n"));
+        assert!(text.contains("ceefax_weather_fetch_failure_total 1\n"));
+        assert!(!text.contains("last_fetch_timestamp_seconds "));
+        assert!(text.contains(
+            "ceefax_weather_temperature_celsius{country=\"uk\",region=\"N. \\\"Ireland\\\"\"} 12.5\n"
+        ));
+    }
+}
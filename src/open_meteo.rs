@@ -0,0 +1,387 @@
+use crate::{
+    cache::DiskCache,
+    config,
+    wttr::{self, Astronomy, CurrentCondition, FetchFailure, Hourly, WeatherClient, WeatherDay, WeatherDesc, WeatherReport},
+};
+use chrono::NaiveDateTime;
+use serde::Deserialize;
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+const FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
+const GEOCODING_URL: &str = "https://geocoding-api.open-meteo.com/v1/search";
+/// Current and hourly fields requested from the forecast API, mapped in `parse_forecast`.
+const CURRENT_FIELDS: &str = "temperature_2m,apparent_temperature,relative_humidity_2m,wind_speed_10m,wind_direction_10m,precipitation,uv_index,pressure_msl,weather_code,is_day";
+const HOURLY_FIELDS: &str = "temperature_2m,dew_point_2m,weather_code,is_day";
+
+#[derive(Deserialize)]
+struct Forecast {
+    current: Current,
+    hourly: HourlySeries,
+    #[serde(default)]
+    daily: Option<DailySeries>,
+}
+
+#[derive(Deserialize)]
+struct Current {
+    temperature_2m: f64,
+    apparent_temperature: f64,
+    #[serde(default)]
+    relative_humidity_2m: Option<f64>,
+    #[serde(default)]
+    uv_index: Option<f64>,
+    #[serde(default)]
+    pressure_msl: Option<f64>,
+    wind_speed_10m: f64,
+    wind_direction_10m: f64,
+    precipitation: f64,
+    weather_code: u8,
+    is_day: u8,
+}
+
+#[derive(Deserialize)]
+struct HourlySeries {
+    time: Vec<String>,
+    temperature_2m: Vec<Option<f64>>,
+    dew_point_2m: Vec<Option<f64>>,
+    weather_code: Vec<Option<u8>>,
+    is_day: Vec<Option<u8>>,
+}
+
+#[derive(Deserialize)]
+struct DailySeries {
+    time: Vec<String>,
+    sunrise: Vec<String>,
+    sunset: Vec<String>,
+    #[serde(default)]
+    temperature_2m_max: Vec<Option<f64>>,
+    #[serde(default)]
+    temperature_2m_min: Vec<Option<f64>>,
+}
+
+#[derive(Deserialize)]
+struct ApiError {
+    reason: String,
+}
+
+#[derive(Deserialize)]
+struct GeocodingResponse {
+    #[serde(default)]
+    results: Vec<Place>,
+}
+
+#[derive(Deserialize)]
+struct Place {
+    latitude: f64,
+    longitude: f64,
+}
+
+/// Fetches forecasts from Open-Meteo, looking region cities up with its geocoding API.
+pub struct OpenMeteoClient {
+    client: reqwest::blocking::Client,
+    cache: Option<DiskCache>,
+    options: config::FetchOptions,
+    /// Coordinates already looked up, so a refresh only fetches forecasts.
+    coordinates: Mutex<HashMap<String, (f64, f64)>>,
+}
+
+impl OpenMeteoClient {
+    pub fn new(cache: Option<DiskCache>, options: &config::FetchOptions) -> Result<Self, String> {
+        let mut builder = reqwest::blocking::Client::builder().timeout(options.timeout);
+        if let Some(url) = &options.proxy {
+            let proxy = reqwest::Proxy::all(url).map_err(|e| format!("Invalid proxy URL '{}': {}", url, e))?;
+            builder = builder.proxy(proxy);
+        }
+        let client = builder
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        Ok(Self {
+            client,
+            cache,
+            options: options.clone(),
+            coordinates: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn get(&self, url: &str, query: &[(&str, String)]) -> Result<String, FetchFailure> {
+        let response = self
+            .client
+            .get(url)
+            .query(query)
+            .send()
+            .map_err(|e| FetchFailure::Transient(wttr::request_error(e, &self.options)))?;
+        if !response.status().is_success() {
+            return Err(wttr::status_failure(response.status()));
+        }
+        response.text().map_err(|e| FetchFailure::Transient(wttr::body_error(e, &self.options)))
+    }
+
+    /// Coordinates for a query, taken as given when it is already `lat,lon`.
+    fn locate(&self, city: &str) -> Result<(f64, f64), FetchFailure> {
+        if let Some(coordinates) = parse_coordinates(city) {
+            return Ok(coordinates);
+        }
+        if let Some(coordinates) = self.coordinates.lock().unwrap().get(city) {
+            return Ok(*coordinates);
+        }
+        let body = self.get(GEOCODING_URL, &[("name", city.to_string()), ("count", "1".to_string())])?;
+        let response: GeocodingResponse = serde_json::from_str(&body)
+            .map_err(|e| FetchFailure::Permanent(format!("Failed to decode Open-Meteo geocoding response: {}", e)))?;
+        let place = response
+            .results
+            .first()
+            .ok_or_else(|| FetchFailure::Permanent(format!("Unknown location '{}'", city)))?;
+        let coordinates = (place.latitude, place.longitude);
+        self.coordinates.lock().unwrap().insert(city.to_string(), coordinates);
+        Ok(coordinates)
+    }
+
+    fn cache_key(city: &str) -> String {
+        format!("open-meteo {}", city)
+    }
+}
+
+impl WeatherClient for OpenMeteoClient {
+    fn fetch(&self, city: &str) -> Result<WeatherReport, String> {
+        match self.cached(city) {
+            Some((report, _)) => Ok(report),
+            None => self.fetch_fresh(city),
+        }
+    }
+
+    fn fetch_fresh(&self, city: &str) -> Result<WeatherReport, String> {
+        let (report, text) = wttr::retry_with_backoff(self.options.retries, std::thread::sleep, || {
+            let (latitude, longitude) = self.locate(city)?;
+            let query = [
+                ("latitude", latitude.to_string()),
+                ("longitude", longitude.to_string()),
+                ("current", CURRENT_FIELDS.to_string()),
+                ("hourly", HOURLY_FIELDS.to_string()),
+                ("daily", "sunrise,sunset,temperature_2m_max,temperature_2m_min".to_string()),
+                ("timezone", "auto".to_string()),
+                ("forecast_days", "3".to_string()),
+            ];
+            let text = self.get(FORECAST_URL, &query)?;
+            let report = parse_forecast(&text).map_err(FetchFailure::Permanent)?;
+            Ok((report, text))
+        })?;
+        if let Some(cache) = &self.cache {
+            // A failed cache write only costs us a refetch next time.
+            let _ = cache.store(&Self::cache_key(city), &text);
+        }
+        Ok(report)
+    }
+
+    fn cached(&self, city: &str) -> Option<(WeatherReport, Duration)> {
+        let (body, age) = self.cache.as_ref()?.load(&Self::cache_key(city))?;
+        parse_forecast(&body).ok().map(|report| (report, age))
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "Open-Meteo"
+    }
+}
+
+/// Reads a `lat,lon` query such as a region fallback of "51.48,-3.18".
+fn parse_coordinates(query: &str) -> Option<(f64, f64)> {
+    let (lat, lon) = query.split_once(',')?;
+    let (lat, lon) = (lat.trim().parse::<f64>().ok()?, lon.trim().parse::<f64>().ok()?);
+    ((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&lon)).then_some((lat, lon))
+}
+
+/// A wttr.in-style description for a WMO weather code, so icons and colors keep working.
+fn describe_weather_code(code: u8, is_day: bool) -> &'static str {
+    match code {
+        0 if is_day => "Sunny",
+        0 => "Clear",
+        1 if is_day => "Mostly sunny",
+        1 => "Mostly clear",
+        2 => "Partly cloudy",
+        3 => "Overcast",
+        45 | 48 => "Fog",
+        51 => "Light drizzle",
+        53 => "Drizzle",
+        55 => "Heavy drizzle",
+        56 | 57 => "Freezing drizzle",
+        61 => "Light rain",
+        63 => "Moderate rain",
+        65 => "Heavy rain",
+        66 | 67 => "Freezing rain",
+        71 | 85 => "Light snow",
+        73 => "Moderate snow",
+        75 | 86 => "Heavy snow",
+        77 => "Snow grains",
+        80 => "Light rain shower",
+        81 => "Moderate rain shower",
+        82 => "Torrential rain shower",
+        95 => "Thunderstorm",
+        96 | 99 => "Thunderstorm with hail",
+        _ => "Unknown",
+    }
+}
+
+/// The 16-point compass direction a wind in degrees blows from.
+fn compass_point(degrees: f64) -> &'static str {
+    const POINTS: [&str; 16] = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW",
+    ];
+    POINTS[(degrees.rem_euclid(360.0) / 22.5).round() as usize % 16]
+}
+
+/// Converts an ISO local time such as "2024-06-01T04:43" to wttr.in's "04:43 AM".
+fn clock_time(iso: &str) -> Option<String> {
+    let time = NaiveDateTime::parse_from_str(iso, "%Y-%m-%dT%H:%M").ok()?;
+    Some(time.format("%I:%M %p").to_string())
+}
+
+fn rounded(value: f64) -> String {
+    (value.round() as i32).to_string()
+}
+
+fn desc(text: &str) -> Vec<WeatherDesc> {
+    vec![WeatherDesc { value: text.to_string() }]
+}
+
+/// Maps an Open-Meteo forecast response onto the wttr.in report structure the views use.
+pub fn parse_forecast(text: &str) -> Result<WeatherReport, String> {
+    let forecast: Forecast = serde_json::from_str(text).map_err(|e| match serde_json::from_str::<ApiError>(text) {
+        Ok(error) => format!("Open-Meteo error: {}", error.reason),
+        Err(_) => format!("Failed to decode Open-Meteo response: {}", e),
+    })?;
+
+    let current = &forecast.current;
+    let current_condition = vec![CurrentCondition {
+        temp_C: rounded(current.temperature_2m),
+        FeelsLikeC: rounded(current.apparent_temperature),
+        windspeedKmph: rounded(current.wind_speed_10m),
+        winddir16Point: compass_point(current.wind_direction_10m).to_string(),
+        precipMM: format!("{:.1}", current.precipitation),
+        humidity: current.relative_humidity_2m.map(rounded).unwrap_or_default(),
+        uvIndex: current.uv_index.map(rounded).unwrap_or_default(),
+        pressure: current.pressure_msl.map(rounded).unwrap_or_default(),
+        // Open-Meteo codes are WMO ones, not WWO, so icons go by the description.
+        weatherCode: String::new(),
+        weatherDesc: desc(describe_weather_code(current.weather_code, current.is_day == 1)),
+    }];
+
+    let series = &forecast.hourly;
+    let mut weather: Vec<WeatherDay> = Vec::new();
+    for (i, time) in series.time.iter().enumerate() {
+        let Some((date, clock)) = time.split_once('T') else {
+            continue;
+        };
+        let (Some(Some(temp)), Some(Some(dew_point))) = (series.temperature_2m.get(i), series.dew_point_2m.get(i)) else {
+            continue;
+        };
+        let code = series.weather_code.get(i).copied().flatten().unwrap_or(u8::MAX);
+        let is_day = series.is_day.get(i).copied().flatten() == Some(1);
+        let Some(minutes) = wttr::slot_minutes(clock) else {
+            continue;
+        };
+        let hourly = Hourly {
+            // wttr.in's unpadded HHMM, e.g. "0" or "1500".
+            time: (minutes / 60 * 100 + minutes % 60).to_string(),
+            tempC: rounded(*temp),
+            DewPointC: rounded(*dew_point),
+            uvIndex: String::new(),
+            weatherCode: String::new(),
+            weatherDesc: desc(describe_weather_code(code, is_day)),
+        };
+        match weather.last_mut() {
+            Some(day) if day.date == date => day.hourly.push(hourly),
+            _ => weather.push(WeatherDay {
+                date: date.to_string(),
+                maxtempC: String::new(),
+                mintempC: String::new(),
+                astronomy: Vec::new(),
+                hourly: vec![hourly],
+            }),
+        }
+    }
+    if let Some(daily) = &forecast.daily {
+        for (i, date) in daily.time.iter().enumerate() {
+            let Some(day) = weather.iter_mut().find(|day| &day.date == date) else {
+                continue;
+            };
+            let extreme = |series: &[Option<f64>]| series.get(i).copied().flatten().map(rounded).unwrap_or_default();
+            day.maxtempC = extreme(&daily.temperature_2m_max);
+            day.mintempC = extreme(&daily.temperature_2m_min);
+            let sun = (daily.sunrise.get(i).and_then(|t| clock_time(t)), daily.sunset.get(i).and_then(|t| clock_time(t)));
+            if let (Some(sunrise), Some(sunset)) = sun {
+                day.astronomy.push(Astronomy {
+                    sunrise,
+                    sunset,
+                    moon_phase: String::new(),
+                    moon_illumination: String::new(),
+                });
+            }
+        }
+    }
+
+    Ok(WeatherReport { current_condition, weather })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Trimmed from a real forecast for London (51.51,-0.13), two days of hourly data.
+    const SAMPLE: &str = r#"{
+        "latitude": 51.5, "longitude": -0.12, "timezone": "Europe/London", "utc_offset_seconds": 3600,
+        "current_units": {"temperature_2m": "°C", "wind_speed_10m": "km/h"},
+        "current": {"time": "2024-06-01T14:00", "interval": 900, "temperature_2m": 17.6, "apparent_temperature": 15.2, "relative_humidity_2m": 71,
+            "wind_speed_10m": 14.4, "wind_direction_10m": 238, "precipitation": 0.1, "uv_index": 3.6, "pressure_msl": 1013.4, "weather_code": 61, "is_day": 1},
+        "hourly": {
+            "time": ["2024-06-01T00:00", "2024-06-01T09:00", "2024-06-01T15:00", "2024-06-02T00:00", "2024-06-02T03:00"],
+            "temperature_2m": [11.2, 14.9, 18.4, 10.6, null],
+            "dew_point_2m": [8.1, 9.0, 9.6, 7.7, 7.5],
+            "weather_code": [0, 2, 80, 3, 45],
+            "is_day": [0, 1, 1, 0, 0]
+        },
+        "daily": {"time": ["2024-06-01", "2024-06-02"], "sunrise": ["2024-06-01T04:46", "2024-06-02T04:45"],
+            "sunset": ["2024-06-01T21:06", "2024-06-02T21:07"], "temperature_2m_max": [19.3, 17.8], "temperature_2m_min": [10.9, null]}
+    }"#;
+
+    #[test]
+    fn test_forecast_maps_onto_report() {
+        let report = parse_forecast(SAMPLE).unwrap();
+
+        let current = &report.current_condition[0];
+        assert_eq!(current.temp_C, "18");
+        assert_eq!(current.FeelsLikeC, "15");
+        assert_eq!(current.windspeedKmph, "14");
+        assert_eq!(current.winddir16Point, "WSW");
+        assert_eq!(current.precipMM, "0.1");
+        assert_eq!(current.humidity, "71");
+        assert_eq!(current.uvIndex, "4");
+        assert_eq!(current.pressure, "1013");
+        assert_eq!(current.weatherDesc[0].value, "Light rain");
+
+        assert_eq!(report.weather.len(), 2);
+        let today = &report.weather[0];
+        assert_eq!(today.date, "2024-06-01");
+        let times: Vec<&str> = today.hourly.iter().map(|h| h.time.as_str()).collect();
+        assert_eq!(times, vec!["0", "900", "1500"]);
+        assert_eq!(today.hourly[2].tempC, "18");
+        assert_eq!(today.hourly[2].DewPointC, "10");
+        assert_eq!(today.hourly[0].weatherDesc[0].value, "Clear");
+        assert_eq!(today.hourly[2].weatherDesc[0].value, "Light rain shower");
+        assert_eq!(today.astronomy[0].sunrise, "04:46 AM");
+        assert_eq!(today.astronomy[0].sunset, "09:06 PM");
+        assert_eq!((today.mintempC.as_str(), today.maxtempC.as_str()), ("11", "19"));
+        assert_eq!(report.weather[1].mintempC, "");
+
+        // The hour with a missing temperature is left out.
+        assert_eq!(report.weather[1].hourly.len(), 1);
+        assert_eq!(wttr::hourly_for_hour(today, 16).unwrap().time, "1500");
+    }
+
+    #[test]
+    fn test_api_errors_and_coordinates() {
+        let err = parse_forecast(r#"{"error": true, "reason": "Latitude must be in range of -90 to 90°."}"#).unwrap_err();
+        assert_eq!(err, "Open-Meteo error: Latitude must be in range of -90 to 90°.");
+        assert_eq!(parse_coordinates("51.48, -3.18"), Some((51.48, -3.18)));
+        assert_eq!(parse_coordinates("CF10"), None);
+        assert_eq!(compass_point(359.0), "N");
+        assert_eq!(compass_point(90.0), "E");
+    }
+}
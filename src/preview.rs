@@ -0,0 +1,147 @@
+use crate::{config, http};
+use ratatui::{
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+use std::{
+    fmt::Write as _,
+    io,
+    net::IpAddr,
+    sync::{Arc, Mutex},
+};
+use unicode_width::UnicodeWidthStr;
+
+/// Seconds between browser reloads of the preview page.
+const RELOAD_SECS: u32 = 30;
+
+/// The most recently rendered frame as an HTML page, shared with the preview server.
+#[derive(Default)]
+pub struct Preview {
+    page: Mutex<String>,
+}
+
+impl Preview {
+    pub fn update(&self, buffer: &Buffer) {
+        *self.page.lock().unwrap() = render_page(buffer);
+    }
+
+    fn page(&self) -> String {
+        self.page.lock().unwrap().clone()
+    }
+}
+
+fn css_color(color: Color) -> Option<String> {
+    let (r, g, b) = match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Black => (0, 0, 0),
+        Color::Red => (170, 0, 0),
+        Color::Green => (0, 170, 0),
+        Color::Yellow => (170, 170, 0),
+        Color::Blue => (0, 0, 170),
+        Color::Magenta => (170, 0, 170),
+        Color::Cyan => (0, 170, 170),
+        Color::Gray => (170, 170, 170),
+        Color::DarkGray => (85, 85, 85),
+        Color::LightRed => (255, 85, 85),
+        Color::LightGreen => (85, 255, 85),
+        Color::LightYellow => (255, 255, 85),
+        Color::LightBlue => (85, 85, 255),
+        Color::LightMagenta => (255, 85, 255),
+        Color::LightCyan => (85, 255, 255),
+        Color::White => (255, 255, 255),
+        Color::Indexed(_) => return None,
+    };
+    Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Converts a rendered buffer to `<pre>` rows of styled `<span>`s, one span per run of equal style.
+pub fn buffer_to_html(buffer: &Buffer) -> String {
+    let area = buffer.area;
+    let mut html = String::from("<pre>");
+    for y in area.top()..area.bottom() {
+        let mut run_style = None;
+        let mut run_text = String::new();
+        let mut skip = 0;
+        for x in area.left()..area.right() {
+            // Wide glyphs such as emoji cover the cells that follow them.
+            if skip > 0 {
+                skip -= 1;
+                continue;
+            }
+            let cell = buffer.get(x, y);
+            skip = cell.symbol().width().saturating_sub(1);
+
+            let mut style = String::new();
+            if let Some(fg) = css_color(cell.fg) {
+                let _ = write!(style, "color:{};", fg);
+            }
+            if let Some(bg) = css_color(cell.bg) {
+                let _ = write!(style, "background:{};", bg);
+            }
+            if cell.modifier.contains(Modifier::BOLD) {
+                style.push_str("font-weight:bold;");
+            }
+            if run_style.as_ref() != Some(&style) {
+                push_span(&mut html, run_style.as_deref(), &run_text);
+                run_text.clear();
+                run_style = Some(style);
+            }
+            run_text.push_str(cell.symbol());
+        }
+        push_span(&mut html, run_style.as_deref(), &run_text);
+        html.push('\n');
+    }
+    html.push_str("</pre>");
+    html
+}
+
+fn push_span(html: &mut String, style: Option<&str>, text: &str) {
+    if text.is_empty() {
+        return;
+    }
+    match style {
+        Some(style) if !style.is_empty() => {
+            let _ = write!(html, "<span style=\"{}\">{}</span>", style, escape_html(text));
+        }
+        _ => html.push_str(&escape_html(text)),
+    }
+}
+
+fn render_page(buffer: &Buffer) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><meta http-equiv=\"refresh\" content=\"{}\">\
+         <title>CEEFAX Weather</title><style>body{{background:{};margin:1em}}pre{{font-family:monospace;line-height:1.1}}</style>\
+         </head><body>{}</body></html>\n",
+        RELOAD_SECS,
+        css_color(config::CEEFAX_BLACK).unwrap_or_default(),
+        buffer_to_html(buffer)
+    )
+}
+
+/// Binds the preview port and serves every request with the latest frame.
+pub fn serve(address: IpAddr, port: u16, preview: Arc<Preview>) -> io::Result<()> {
+    http::serve(address, port, "text/html; charset=utf-8", move || preview.page())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::{layout::Rect, style::Style};
+
+    #[test]
+    fn test_buffer_to_html_groups_styled_runs() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 1));
+        buffer.set_string(0, 0, "<a>", Style::default().fg(config::CEEFAX_YELLOW));
+        buffer.set_string(3, 0, "☔", Style::default().fg(config::CEEFAX_RED));
+
+        assert_eq!(
+            buffer_to_html(&buffer),
+            "<pre><span style=\"color:#cccc00;\">&lt;a&gt;</span><span style=\"color:#cc0000;\">☔</span> \n</pre>"
+        );
+    }
+}
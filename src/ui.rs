@@ -1,15 +1,93 @@
-use crate::{app::AppData, config, wttr};
-use chrono::{DateTime, Local};
+use crate::{app::{AppData, Summary}, config, wttr};
+use chrono::{DateTime, Local, NaiveDate, Timelike};
 use ratatui::{
-    layout::{Alignment, Constraint, Direction, Layout},
-    style::{Style, Stylize},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style, Stylize},
     text::{Line, Span, Text},
     widgets::{Block, Padding, Paragraph, Wrap},
     Frame,
 };
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::Range,
+    time::Duration,
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// The style to draw with in `mode`: as designed, mapped onto ANSI colors, or plain when color is off.
+pub fn mode_style(style: Style, mode: config::ColorMode) -> Style {
+    match mode {
+        config::ColorMode::Truecolor => style,
+        config::ColorMode::Ansi16 => Style {
+            fg: style.fg.map(config::to_ansi16),
+            bg: style.bg.map(config::to_ansi16),
+            ..style
+        },
+        config::ColorMode::Monochrome => Style::default(),
+    }
+}
+
+/// Rewrites every cell's colors for the active color mode once a frame has been drawn.
+pub fn apply_color_mode(f: &mut Frame, mode: config::ColorMode) {
+    if mode == config::ColorMode::Truecolor {
+        return;
+    }
+    let area = f.size();
+    let buffer = f.buffer_mut();
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let cell = buffer.get_mut(x, y);
+            let style = mode_style(cell.style(), mode);
+            cell.fg = style.fg.unwrap_or(Color::Reset);
+            cell.bg = style.bg.unwrap_or(Color::Reset);
+            cell.modifier = style.add_modifier;
+        }
+    }
+}
 
-pub fn loading_ui(f: &mut Frame, counter: u16) {
+/// Repaints a drawn frame in the theme's colors.
+pub fn apply_theme(f: &mut Frame, theme: &config::Theme) {
+    if *theme == config::CEEFAX_THEME {
+        return;
+    }
+    let area = f.size();
+    let buffer = f.buffer_mut();
+    for y in area.top()..area.bottom() {
+        for x in area.left()..area.right() {
+            let cell = buffer.get_mut(x, y);
+            cell.fg = theme.recolor(cell.fg);
+            cell.bg = theme.recolor(cell.bg);
+        }
+    }
+}
+
+/// Rows of an `height`-row page painted in after `elapsed` of a reveal.
+pub fn revealed_rows(height: u16, elapsed: Duration) -> u16 {
+    let progress = elapsed.as_secs_f64() / config::REVEAL_DURATION.as_secs_f64();
+    (f64::from(height) * progress.min(1.0)).ceil() as u16
+}
+
+/// Blanks the rows below the ones revealed so far, so the page appears to paint in from the top.
+pub fn apply_reveal(f: &mut Frame, elapsed: Duration) {
+    let area = f.size();
+    let first_hidden = area.top() + revealed_rows(area.height, elapsed);
+    let buffer = f.buffer_mut();
+    for y in first_hidden..area.bottom() {
+        for x in area.left()..area.right() {
+            buffer.get_mut(x, y).reset();
+            buffer.get_mut(x, y).set_bg(config::CEEFAX_BLACK);
+        }
+    }
+}
+
+/// Paints the whole frame in the page background. Called once per frame before the page
+/// itself, which then only draws its own text over it.
+pub fn clear_page(f: &mut Frame) {
+    let background = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLUE);
+    f.render_widget(Block::default().style(background), f.size());
+}
+
+pub fn loading_ui(f: &mut Frame, options: &config::DisplayOptions, counter: u16, elapsed: Duration, fetching: Option<&str>) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(1), Constraint::Min(1)])
@@ -18,14 +96,10 @@ pub fn loading_ui(f: &mut Frame, counter: u16) {
     let title_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLACK);
     let time_style = Style::default().fg(config::CEEFAX_YELLOW).bg(config::CEEFAX_BLACK);
     let left_text = format!("P{} SEARCHING...", counter);
-    let date_text = Local::now().format("%a %d %b").to_string().to_uppercase();
+    let date_text = header_date(options);
     let time_text = Local::now().format("%H:%M/%S").to_string();
     
-    let full_right_text_len = date_text.len() + time_text.len() + 3;
-    let padding_len = if f.size().width as usize > left_text.len() + full_right_text_len {
-        f.size().width as usize - left_text.len() - full_right_text_len
-    } else { 0 };
-    let padding = " ".repeat(padding_len);
+    let padding = " ".repeat(header_padding(f.size().width, &left_text, &date_text, &time_text));
 
     let header_line = Line::from(vec![
         Span::styled(left_text, title_style.bold()),
@@ -36,11 +110,27 @@ pub fn loading_ui(f: &mut Frame, counter: u16) {
     ]);
     let header_widget = Paragraph::new(header_line);
 
-    let loading_body = Paragraph::new("\n\n\nSearching...")
+    let elapsed_color = if elapsed > config::SLOW_FETCH_THRESHOLD {
+        config::CEEFAX_YELLOW
+    } else {
+        config::CEEFAX_WHITE
+    };
+    let loading_text = vec![
+        Line::from(""),
+        Line::from(""),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw(match fetching {
+                Some(region) => format!("Fetching {}... ", region),
+                None => "Searching... ".to_string(),
+            }),
+            Span::styled(format!("({}s)", elapsed.as_secs()), Style::default().fg(elapsed_color)),
+        ]),
+    ];
+    let loading_body = Paragraph::new(loading_text)
         .style(Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLUE))
         .alignment(Alignment::Center);
 
-    f.render_widget(Block::default().style(Style::default().bg(config::CEEFAX_BLUE)), f.size());
     f.render_widget(header_widget, chunks[0]);
     f.render_widget(loading_body, chunks[1]);
 }
@@ -64,13 +154,67 @@ pub fn error_ui(f: &mut Frame, error: &str) {
     
     let footer_widget = Paragraph::new("[R]etry      [Q]uit").style(blue_bg_style);
 
-    f.render_widget(Block::default().style(blue_bg_style), f.size());
     f.render_widget(header_widget, chunks[0]);
     f.render_widget(error_body, chunks[1]);
     f.render_widget(footer_widget, chunks[2]);
 }
 
-pub fn main_ui(f: &mut Frame, data: &AppData, updated_at: &DateTime<Local>) {
+/// The size of a scrolling page's list as last drawn, so paging keys know how far to move.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
+pub struct ListExtent {
+    /// Rows in the whole list.
+    pub rows: usize,
+    /// Rows that fit on screen at once.
+    pub visible: u16,
+}
+
+impl ListExtent {
+    /// The furthest the list can scroll while its last row still fills the bottom of the screen.
+    pub fn max_scroll(&self) -> usize {
+        self.rows.saturating_sub(self.visible as usize)
+    }
+
+    /// The extent of `list` drawn into `area` inside `block`, with rows counted the way the
+    /// paragraph lays them out, wrapping included.
+    fn of(list: &Paragraph, block: &Block, area: Rect) -> Self {
+        let inner = block.inner(area);
+        Self { rows: list.line_count(inner.width), visible: inner.height }
+    }
+}
+
+/// Runtime toggles and transient messages for the main page.
+#[derive(Default)]
+pub struct MainPageState {
+    pub show_legend: bool,
+    /// The displayed data is older than the `--stale-after` threshold.
+    pub stale: bool,
+    /// Time left before the next auto-refresh, shown as a countdown when known.
+    pub next_refresh: Option<Duration>,
+    /// Auto-refresh is paused; manual refreshes still work.
+    pub paused: bool,
+    pub show_attribution: bool,
+    /// Overlay wind direction arrows next to the map temperatures.
+    pub show_wind: bool,
+    pub show_fetch_time: bool,
+    pub provider: &'static str,
+    pub notice: Option<String>,
+    /// Region whose summary is listed first.
+    pub pinned_region: Option<String>,
+}
+
+/// A countdown as "M:SS", rounding part seconds up so it reads 0:00 only once time is up.
+pub fn format_refresh_countdown(remaining: Duration) -> String {
+    let secs = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// Summaries in display order, with the pinned region's moved to the top.
+fn ordered_summaries<'a>(summaries: &'a [Summary], pinned: Option<&str>) -> Vec<&'a Summary> {
+    let is_pinned = |summary: &&Summary| pinned.is_some_and(|name| name.eq_ignore_ascii_case(&summary.region));
+    summaries.iter().filter(is_pinned).chain(summaries.iter().filter(|s| !is_pinned(s))).collect()
+}
+
+pub fn main_ui(f: &mut Frame, data: &AppData, options: &config::DisplayOptions, updated_at: &DateTime<Local>, page: &MainPageState) {
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(1), Constraint::Min(1), Constraint::Length(2)])
@@ -81,9 +225,10 @@ pub fn main_ui(f: &mut Frame, data: &AppData, updated_at: &DateTime<Local>) {
         .constraints([Constraint::Percentage(45), Constraint::Percentage(55)])
         .split(main_chunks[1]);
 
+    let legend_height = if page.show_legend { options.temp_scheme.bands().len() as u16 + 1 } else { 0 };
     let left_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([Constraint::Length(8), Constraint::Min(10)])
+        .constraints([Constraint::Length(8), Constraint::Min(1), Constraint::Length(legend_height)])
         .split(content_chunks[0]);
 
     let right_chunks = Layout::default()
@@ -94,14 +239,10 @@ pub fn main_ui(f: &mut Frame, data: &AppData, updated_at: &DateTime<Local>) {
     let title_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLACK);
     let time_style = Style::default().fg(config::CEEFAX_YELLOW).bg(config::CEEFAX_BLACK);
     let left_text = "P181 CEEFAX 181";
-    let date_text = Local::now().format("%a %d %b").to_string().to_uppercase();
+    let date_text = header_date(options);
     let time_text = Local::now().format("%H:%M/%S").to_string();
     
-    let full_right_text_len = date_text.len() + time_text.len() + 3;
-    let padding_len = if f.size().width as usize > left_text.len() + full_right_text_len {
-        f.size().width as usize - left_text.len() - full_right_text_len
-    } else { 0 };
-    let padding = " ".repeat(padding_len);
+    let padding = " ".repeat(header_padding(f.size().width, left_text, &date_text, &time_text));
 
     let header_line = Line::from(vec![
         Span::styled(left_text, title_style),
@@ -116,36 +257,93 @@ pub fn main_ui(f: &mut Frame, data: &AppData, updated_at: &DateTime<Local>) {
     let title_widget = Paragraph::new(config::WEATHER_TITLE).style(blue_bg_style.bold());
     
     let (left_desc, left_icon) = &data.left_text;
-    let left_text_widget = Paragraph::new(format!("{} {}", left_icon, left_desc))
+    let mut left_lines = vec![Line::from(format!("{} {}", left_icon, left_desc))];
+    if !data.outlook.is_empty() {
+        left_lines.push(Line::from(""));
+        left_lines.push(Line::styled("COMING UP", Style::default().fg(config::CEEFAX_CYAN)));
+        left_lines.push(Line::from(data.outlook.join(" ")));
+    }
+    let left_text_widget = Paragraph::new(left_lines)
         .style(blue_bg_style)
         .wrap(Wrap { trim: true });
         
-    let summary_lines: Vec<Line> = data.summaries.iter()
-        .map(|(desc, icon)| Line::from(format!("{} {}", icon, desc)))
+    let pinned = page.pinned_region.as_deref();
+    let summary_lines: Vec<Line> = ordered_summaries(&data.summaries, pinned).into_iter()
+        .map(|summary| {
+            let mut style = Style::default().fg(summary.color);
+            if pinned.is_some_and(|name| name.eq_ignore_ascii_case(&summary.region)) {
+                style = style.bold();
+            }
+            Line::styled(format!("{} {}", summary.icon, summary_text(summary, options)), style)
+        })
         .collect();
     let right_text_widget = Paragraph::new(Text::from(summary_lines)).style(blue_bg_style);
 
-    let map_widget = draw_map_widget(&data.country, &data.reports);
+    let map_scale = if options.fit_map { fit_map_scale(&data.country.map_template, right_chunks[1]) } else { 1.0 };
+    let map_widget = draw_map_widget(&data.country, &data.reports, options, map_scale, page.show_wind);
     
     let (footer_desc, footer_icon) = &data.footer_text;
-    let footer_text = format!(
-        "[C]ountry [D]etails [R]efresh      Updated: {}      {} {}",
-        updated_at.format("%H:%M:%S"),
-        footer_icon,
-        footer_desc
-    );
-    let footer_widget = Paragraph::new(footer_text).style(blue_bg_style);
+    // Key hints on the first footer row; status (or a transient notice) on the second.
+    let key_hints = "[C]ountry [D]etails [F]orecast [W]arm/[X]cold [G]raphic [H]istory Ra[N]king [L]egend [B]ands [I]nspect [T]op [V]ane [A]ttrib [R]efresh [P]ause [S]hare";
+    let status_line = match &page.notice {
+        Some(notice) => Line::from(Span::styled(notice.clone(), Style::default().fg(config::CEEFAX_YELLOW))),
+        None => {
+            let mut status_spans = vec![Span::raw(format!("Updated: {}", updated_at.format("%H:%M:%S")))];
+            if page.paused {
+                status_spans.push(Span::styled("  PAUSED", Style::default().fg(config::CEEFAX_YELLOW).bold()));
+            } else if let Some(remaining) = page.next_refresh {
+                status_spans.push(Span::raw(format!("  Next: {}", format_refresh_countdown(remaining))));
+            }
+            if page.stale {
+                status_spans.push(Span::styled(" STALE", Style::default().fg(config::CEEFAX_RED).bold()));
+            }
+            if let Some(fetch_duration) = data.fetch_duration.filter(|_| page.show_fetch_time) {
+                status_spans.push(Span::raw(format!(" (fetched in {:.1}s)", fetch_duration.as_secs_f32())));
+            }
+            status_spans.push(Span::raw(format!("      {} {}", footer_icon, footer_desc)));
+            let featured_report = data.country.footer_region().and_then(|region| data.reports.get(&region.name));
+            if let Some((event, remaining)) = featured_report.and_then(|report| wttr::next_sun_event(report, Local::now().naive_local())) {
+                let style = if event == "Sunset" && remaining < chrono::Duration::hours(1) {
+                    Style::default().fg(config::SUNSET_ORANGE)
+                } else {
+                    Style::default()
+                };
+                status_spans.push(Span::styled(format!("      {} in {}", event, wttr::format_countdown(remaining)), style));
+            }
+            if page.show_attribution {
+                status_spans.push(Span::raw(format!("      Data: {}", page.provider)));
+            }
+            Line::from(status_spans)
+        }
+    };
+    let footer_lines = vec![Line::from(key_hints), status_line];
+    let footer_widget = Paragraph::new(footer_lines).style(blue_bg_style);
 
-    f.render_widget(Block::default().style(blue_bg_style), f.size());
     f.render_widget(header_widget, main_chunks[0]);
     f.render_widget(title_widget, left_chunks[0]);
     f.render_widget(left_text_widget, left_chunks[1]);
     f.render_widget(right_text_widget, right_chunks[0]);
     f.render_widget(map_widget, right_chunks[1]);
     f.render_widget(footer_widget, main_chunks[2]);
+    if page.show_legend {
+        f.render_widget(legend_widget(options.temp_scheme, options.units.scale()), left_chunks[2]);
+    }
 }
 
-pub fn details_ui(f: &mut Frame, data: &AppData, scroll: u16) {
+/// Builds the map key from the active temperature bands.
+fn legend_widget<'a>(scheme: wttr::TempScheme, scale: wttr::TempScale) -> Paragraph<'a> {
+    let blue_bg_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLUE);
+    let mut lines = vec![Line::from(Span::styled(format!("KEY ({})", scheme.name()), blue_bg_style.bold()))];
+    for (index, band) in scheme.bands().iter().enumerate() {
+        lines.push(Line::from(vec![
+            Span::styled("  ", Style::new().bg(band.color)),
+            Span::styled(format!(" {}", wttr::band_label(scheme, index, scale)), blue_bg_style),
+        ]));
+    }
+    Paragraph::new(lines).style(blue_bg_style)
+}
+
+pub fn details_ui(f: &mut Frame, data: &AppData, options: &config::DisplayOptions, scroll: u16) -> ListExtent {
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)])
@@ -158,80 +356,316 @@ pub fn details_ui(f: &mut Frame, data: &AppData, scroll: u16) {
     let mut details_text = Vec::new();
     for (i, region) in data.country.regions.iter().enumerate() {
         if let Some(report) = data.reports.get(&region.name) {
-            let condition = &report.current_condition[0];
-            let desc = &condition.weatherDesc[0].value;
-            let icon = wttr::get_weather_icon(desc);
-            let title = format!("{}. -- {} --", i + 1, region.name);
-
+            let title = format!("{}. -- {} --", i + 1, region.title());
             details_text.push(Line::from(Span::styled(title, Style::default().fg(config::CEEFAX_YELLOW).bold())));
-            details_text.push(Line::from(format!("   {} {}", icon, desc)));
-            details_text.push(Line::from(format!("   Feels Like: {}°C", condition.FeelsLikeC)));
-            details_text.push(Line::from(format!("   Wind: {} {} km/h", condition.winddir16Point, condition.windspeedKmph)));
-            details_text.push(Line::from(format!("   Precip: {} mm", condition.precipMM)));
+            let updated_at = data.region_updated.get(&region.name);
+            let pressure_trend = data.pressure_trends.get(&region.name).copied();
+            details_text.extend(region_detail_lines(report, options, updated_at, pressure_trend));
+            details_text.push(Line::from(" "));
+        } else if let Some(error) = data.failures.get(&region.name) {
+            let title = format!("{}. -- {} --", i + 1, region.title());
+            details_text.push(Line::from(Span::styled(title, Style::default().fg(config::CEEFAX_YELLOW).bold())));
+            details_text.push(Line::from(format!("   N/A: {}", error)));
             details_text.push(Line::from(" "));
         }
     }
     
     let blue_bg_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLUE);
+    let details_block = Block::default();
     let details_widget = Paragraph::new(details_text)
         .style(blue_bg_style)
-        .block(Block::default().style(blue_bg_style))
         .wrap(Wrap { trim: true })
         .scroll((scroll, 0));
+    let extent = ListExtent::of(&details_widget, &details_block, main_chunks[1]);
+    let details_widget = details_widget.block(details_block);
 
-    let footer_widget = Paragraph::new("Select number for [H]ourly forecast, [M]ap View").style(blue_bg_style);
+    let footer_text = format!(
+        "Select number for [H]ourly forecast, [M]ap View{}",
+        unselectable_note(data.country.regions.len())
+    );
+    let footer_widget = Paragraph::new(footer_text).style(blue_bg_style);
 
-    f.render_widget(Block::default().style(blue_bg_style), f.size());
     f.render_widget(header_widget, main_chunks[0]);
     f.render_widget(details_widget, main_chunks[1]);
     f.render_widget(footer_widget, main_chunks[2]);
+    extent
 }
 
-pub fn hourly_ui(f: &mut Frame, data: &AppData, region_index: usize, scroll: u16) {
+/// The details page lines under a region's title. Conditions missing from a truncated response show as "--".
+fn region_detail_lines<'a>(
+    report: &wttr::WeatherReport,
+    options: &config::DisplayOptions,
+    updated_at: Option<&DateTime<Local>>,
+    pressure_trend: Option<char>,
+) -> Vec<Line<'a>> {
+    let mut lines = Vec::new();
+    match report.current_condition.first() {
+        Some(condition) => {
+            let desc = condition.weatherDesc.first().map_or("--", |desc| desc.value.as_str());
+            let icon = wttr::condition_marker(wttr::icon_description(&condition.weatherCode, desc), options.text_only, options.icons);
+            lines.push(Line::from(format!("   {} {}", icon, desc)));
+            lines.push(Line::from(format!("   Feels Like: {}", wttr::format_temp(&condition.FeelsLikeC, options.units))));
+            // An unreadable speed is described as calm.
+            let (force, force_name) = wttr::beaufort(condition.windspeedKmph.trim().parse().unwrap_or(0));
+            lines.push(Line::from(format!(
+                "   Wind: {} {} {} (F{} {})",
+                condition.winddir16Point,
+                wttr::wind_arrow(&condition.winddir16Point).unwrap_or('·'),
+                wttr::format_wind(&condition.windspeedKmph, options.units),
+                force,
+                force_name
+            )));
+            lines.push(Line::from(format!("   Precip: {} mm", condition.precipMM)));
+            if !condition.humidity.is_empty() {
+                lines.push(Line::from(format!("   Humidity: {}%", condition.humidity)));
+            }
+            if !condition.pressure.is_empty() {
+                let trend = pressure_trend.map_or(String::new(), |arrow| format!(" {}", arrow));
+                lines.push(Line::from(format!("   Pressure: {} mb{}", condition.pressure, trend)));
+            }
+            if !condition.uvIndex.is_empty() {
+                let (band, color) = wttr::uv_band(condition.uvIndex.trim().parse().unwrap_or(0));
+                lines.push(Line::from(vec![
+                    Span::raw(format!("   UV: {} ", condition.uvIndex)),
+                    Span::styled(band, Style::default().fg(color)),
+                ]));
+            }
+        }
+        None => lines.push(Line::from("   --")),
+    }
+    if let Some(today) = report.weather.first() {
+        lines.push(Line::from(format!("   Today: {}", wttr::day_variability(&today.hourly))));
+    }
+    if let Some(astronomy) = report.weather.first().and_then(|today| today.astronomy.first()) {
+        lines.push(Line::from(format!(
+            "   Sunrise: {}  Sunset: {}",
+            wttr::sun_clock(&astronomy.sunrise),
+            wttr::sun_clock(&astronomy.sunset)
+        )));
+        if !astronomy.moon_phase.is_empty() {
+            let icon = wttr::moon_phase_icon(&astronomy.moon_phase);
+            let show_icon = !icon.is_empty() && !options.text_only && options.icons == config::IconSet::Emoji;
+            let icon = if show_icon { format!("{} ", icon) } else { String::new() };
+            let illumination = astronomy.moon_illumination.trim();
+            let illumination = if illumination.is_empty() { String::new() } else { format!(" ({}%)", illumination) };
+            lines.push(Line::from(format!("   Moon: {}{}{}", icon, astronomy.moon_phase, illumination)));
+        }
+    }
+    if let Some(updated_at) = updated_at {
+        let age_minutes = (Local::now() - *updated_at).num_minutes().max(0);
+        lines.push(Line::from(format!("   Updated: {} ({} min ago)", updated_at.format("%H:%M"), age_minutes)));
+    }
+    if let Some(hourly) = report.weather.first().and_then(|today| wttr::hourly_for_hour(today, Local::now().hour())) {
+        lines.push(Line::from(vec![
+            Span::raw("   Dew Point: "),
+            dewpoint_span(&hourly.DewPointC, options.units),
+        ]));
+    }
+    lines
+}
+
+pub fn hourly_ui(
+    f: &mut Frame,
+    data: &AppData,
+    options: &config::DisplayOptions,
+    region_index: usize,
+    day: usize,
+    scroll: u16,
+    goto: Option<&str>,
+) -> ListExtent {
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)])
         .split(f.size());
 
     let region = &data.country.regions[region_index];
+    let selected_day = data.reports.get(&region.name).and_then(|report| report.weather.get(day));
     let title_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLACK);
-    let header_text = format!("P183 Hourly Forecast for {}", region.name);
+    let header_text = match selected_day {
+        Some(weather_day) => format!("P183 Hourly Forecast for {} - {}", region.name, weather_day.date),
+        None => format!("P183 Hourly Forecast for {}", region.name),
+    };
     let header_widget = Paragraph::new(header_text).style(title_style.bold());
 
     let mut hourly_text = vec![Line::from("")];
-    if let Some(report) = data.reports.get(&region.name) {
-        if let Some(today) = report.weather.first() {
-            for hourly_data in &today.hourly {
-                let time_f = hourly_data.time.parse::<i32>().unwrap_or(0) / 100;
-                let desc = &hourly_data.weatherDesc[0].value;
-                let icon = wttr::get_weather_icon(desc);
-                let line = format!(
-                    "  {:02}:00 - {}°C - {} {}",
-                    time_f,
-                    hourly_data.tempC,
-                    icon,
-                    desc
-                );
-                hourly_text.push(Line::from(line));
+    if let Some(weather_day) = selected_day {
+        for hourly_data in &weather_day.hourly {
+            let time_label = hourly_data.minutes().map_or_else(|| "--:--".to_string(), wttr::format_slot_time);
+            let desc = hourly_data.weatherDesc.first().map_or("N/A", |d| d.value.as_str());
+            let icon = wttr::condition_marker(wttr::icon_description(&hourly_data.weatherCode, desc), options.text_only, options.icons);
+            let temp_style = match wttr::parse_temp_rounded(&hourly_data.tempC) {
+                Some(temp) => Style::default().fg(wttr::get_temp_color(temp, wttr::TempScale::Celsius, options.temp_scheme)),
+                None => Style::default(),
+            };
+            let mut spans = vec![
+                Span::raw(format!("  {} - ", time_label)),
+                Span::styled(wttr::format_temp(&hourly_data.tempC, options.units), temp_style),
+                Span::raw(format!(" - {} {}", icon, desc)),
+                Span::raw(" - Dew "),
+                dewpoint_span(&hourly_data.DewPointC, options.units),
+            ];
+            if !hourly_data.uvIndex.is_empty() {
+                let (_, color) = wttr::uv_band(hourly_data.uvIndex.trim().parse().unwrap_or(0));
+                spans.push(Span::raw(" - UV "));
+                spans.push(Span::styled(hourly_data.uvIndex.clone(), Style::default().fg(color)));
             }
+            hourly_text.push(Line::from(spans));
         }
     }
 
     let blue_bg_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLUE);
-    let hourly_widget = Paragraph::new(hourly_text)
-        .style(blue_bg_style)
-        .block(Block::default().style(blue_bg_style))
-        .scroll((scroll, 0));
+    let hourly_block = Block::default();
+    let hourly_widget = Paragraph::new(hourly_text).style(blue_bg_style).scroll((scroll, 0));
+    let extent = ListExtent::of(&hourly_widget, &hourly_block, main_chunks[1]);
+    let hourly_widget = hourly_widget.block(hourly_block);
 
-    let footer_widget = Paragraph::new("[D]etails View").style(blue_bg_style);
+    let footer_text = match goto {
+        Some(input) if !input.is_empty() && wttr::parse_clock_time(input).is_none() => {
+            format!("Go to time: {}_  NOT A VALID TIME", input)
+        }
+        Some(input) => format!("Go to time (e.g. 15 or 15:30), Enter to jump: {}_", input),
+        None => "[ ] Previous/Next Day   [G]o to Time   [D]etails View".to_string(),
+    };
+    let footer_widget = Paragraph::new(footer_text).style(blue_bg_style);
 
-    f.render_widget(Block::default().style(blue_bg_style), f.size());
     f.render_widget(header_widget, main_chunks[0]);
     f.render_widget(hourly_widget, main_chunks[1]);
     f.render_widget(footer_widget, main_chunks[2]);
+    extent
+}
+
+fn dewpoint_span<'a>(dewpoint: &str, units: config::Units) -> Span<'a> {
+    match dewpoint.parse::<i32>() {
+        Ok(d) => Span::styled(wttr::format_temp(dewpoint, units), Style::default().fg(wttr::dewpoint_color(d))),
+        Err(_) => Span::raw("--"),
+    }
+}
+
+pub fn big_icon_ui(f: &mut Frame, data: &AppData, options: &config::DisplayOptions) {
+    let main_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)])
+        .split(f.size());
+
+    let region = data.country.footer_region();
+    let condition = region
+        .and_then(|region| data.reports.get(&region.name))
+        .and_then(|report| report.current_condition.first());
+
+    let title_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLACK);
+    let header_text = format!("P184 Picture Page {}", region.map_or("", |region| region.name.as_str()));
+    let header_widget = Paragraph::new(header_text).style(title_style.bold());
+
+    let desc = condition
+        .and_then(|condition| condition.weatherDesc.first())
+        .map_or("Weather unavailable", |desc| desc.value.as_str());
+    let code = condition.map_or("", |condition| condition.weatherCode.as_str());
+    let icon = wttr::get_weather_icon(wttr::icon_description(code, desc), config::IconSet::Emoji);
+    let art_color = match icon {
+        "☀️" | "⛅" => config::CEEFAX_YELLOW,
+        "🌦️" | "🌧️" | "🌩️" => config::CEEFAX_CYAN,
+        _ => config::CEEFAX_WHITE,
+    };
+
+    // Pad every row to the same width so centering keeps the art's shape intact.
+    let art = config::big_icon_art(icon).trim_matches('\n');
+    let art_width = art.lines().map(|line| line.chars().count()).max().unwrap_or(0);
+    let mut picture: Vec<Line> = art
+        .lines()
+        .map(|line| Line::from(Span::styled(format!("{:<width$}", line, width = art_width), Style::default().fg(art_color).bold())))
+        .collect();
+    let art_height = picture.len() + 3;
+    picture.push(Line::from(""));
+    if let Some(condition) = condition {
+        let color = wttr::parse_temp_rounded(&condition.temp_C)
+            .map_or(config::CEEFAX_WHITE, |temp| wttr::get_temp_color(temp, wttr::TempScale::Celsius, options.temp_scheme));
+        picture.push(Line::from(Span::styled(
+            wttr::format_temp(&condition.temp_C, options.units),
+            Style::default().fg(color).bold(),
+        )));
+    }
+    picture.push(Line::from(desc.to_string()));
+
+    let top_padding = (main_chunks[1].height as usize).saturating_sub(art_height) / 2;
+    let mut page_text = vec![Line::from(""); top_padding];
+    page_text.extend(picture);
+
+    let blue_bg_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLUE);
+    let picture_widget = Paragraph::new(page_text)
+        .style(blue_bg_style)
+        .alignment(Alignment::Center);
+
+    let footer_widget = Paragraph::new("[M]ap View").style(blue_bg_style);
+
+    f.render_widget(header_widget, main_chunks[0]);
+    f.render_widget(picture_widget, main_chunks[1]);
+    f.render_widget(footer_widget, main_chunks[2]);
+}
+
+pub fn colortest_ui(f: &mut Frame, scheme: wttr::TempScheme) {
+    let main_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)])
+        .split(f.size());
+
+    let title_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLACK);
+    let header_widget = Paragraph::new("P199 Colour Test").style(title_style.bold());
+
+    let blue_bg_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLUE);
+    let swatch = |label: &str, color| {
+        Line::from(vec![
+            Span::styled("        ", Style::new().bg(color)),
+            Span::styled(format!(" {:<12} {:?}", label, color), blue_bg_style),
+        ])
+    };
+
+    let mut lines = vec![Line::from(Span::styled("PALETTE", blue_bg_style.bold()))];
+    for (name, color) in config::CEEFAX_PALETTE.iter() {
+        lines.push(swatch(name, *color));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(format!("TEMPERATURE BANDS ({})", scheme.name()), blue_bg_style.bold())));
+    for (index, band) in scheme.bands().iter().enumerate() {
+        let label = wttr::band_label(scheme, index, wttr::TempScale::Celsius);
+        lines.push(swatch(&label, band.color));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from("Each swatch should be a distinct solid colour. If they look alike,"));
+    lines.push(Line::from("your terminal may not support truecolor (check $COLORTERM)."));
+
+    let swatches_widget = Paragraph::new(lines)
+        .style(blue_bg_style)
+        .block(Block::default().padding(Padding::new(2, 2, 1, 1)));
+    let footer_widget = Paragraph::new("[B]ands  Any other key to exit").style(blue_bg_style);
+
+    f.render_widget(header_widget, main_chunks[0]);
+    f.render_widget(swatches_widget, main_chunks[1]);
+    f.render_widget(footer_widget, main_chunks[2]);
 }
 
-pub fn select_country_ui(f: &mut Frame, available: &[String], scroll: u16) {
+/// A summary line's text; text-only mode adds the temperature and its band word in the
+/// scheme currently chosen, since it can change after the data was fetched.
+fn summary_text(summary: &Summary, options: &config::DisplayOptions) -> String {
+    let temp = summary.temp_c.as_deref().and_then(|temp_c| Some((temp_c, wttr::parse_temp_rounded(temp_c)?)));
+    match temp {
+        Some((temp_c, temp)) if options.text_only => {
+            let band = wttr::get_temp_band(temp, wttr::TempScale::Celsius, options.temp_scheme);
+            format!("{} ({} {})", summary.text, band.word, wttr::format_temp(temp_c, options.units))
+        }
+        _ => summary.text.clone(),
+    }
+}
+
+/// A footer note naming the list items past 9, which no digit key can select.
+pub fn unselectable_note(count: usize) -> String {
+    match count {
+        n if n <= config::DIGIT_KEYS => String::new(),
+        n if n == config::DIGIT_KEYS + 1 => format!("  ({} not selectable)", n),
+        n => format!("  ({}-{} not selectable)", config::DIGIT_KEYS + 1, n),
+    }
+}
+
+pub fn select_country_ui(f: &mut Frame, available: &[String], selected: usize) -> ListExtent {
     let main_chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)])
@@ -242,36 +676,281 @@ pub fn select_country_ui(f: &mut Frame, available: &[String], scroll: u16) {
     let header_widget = Paragraph::new(header_text).style(title_style.bold());
 
     let mut country_list_text = vec![Line::from(""), Line::from("Select Country:"), Line::from("")];
+    let first_row = country_list_text.len();
+    let highlight_style = Style::default().fg(config::CEEFAX_BLUE).bg(config::CEEFAX_WHITE);
     for (i, country_name) in available.iter().enumerate() {
-        let line = format!("{}. {}", i + 1, country_name);
-        country_list_text.push(Line::from(line));
+        let line = Line::from(format!("{}. {}", i + 1, country_name));
+        country_list_text.push(if i == selected { line.style(highlight_style) } else { line });
     }
 
+    // Inside the top and bottom padding.
+    let visible_rows = main_chunks[1].height.saturating_sub(2);
     let blue_bg_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLUE);
     let list_widget = Paragraph::new(country_list_text)
         .style(blue_bg_style)
         .block(Block::default().padding(Padding::new(2, 2, 1, 1)))
-        .scroll((scroll, 0));
+        .scroll((scroll_to_show(first_row + selected, visible_rows), 0));
 
-    let footer_widget = Paragraph::new("[M]ap View").style(blue_bg_style);
+    let footer_widget = Paragraph::new("[M]ap View  [Up/Down] Move  [Enter] Select").style(blue_bg_style);
 
-    f.render_widget(Block::default().style(blue_bg_style), f.size());
     f.render_widget(header_widget, main_chunks[0]);
     f.render_widget(list_widget, main_chunks[1]);
     f.render_widget(footer_widget, main_chunks[2]);
+    ListExtent { rows: available.len(), visible: visible_rows }
+}
+
+/// The scroll offset that brings `row` into a view `visible_rows` tall, scrolling no further than needed.
+fn scroll_to_show(row: usize, visible_rows: u16) -> u16 {
+    (row + 1).saturating_sub(visible_rows.max(1) as usize) as u16
+}
+
+/// One line per forecast day for a region, e.g. "Sat 01 Jun   9°C / 21°C  ☀️ Sunny",
+/// with the condition taken from the midday slot.
+pub fn forecast_lines<'a>(report: &wttr::WeatherReport, options: &config::DisplayOptions) -> Vec<Line<'a>> {
+    report
+        .weather
+        .iter()
+        .map(|day| {
+            let date = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d")
+                .map_or_else(|_| day.date.clone(), |date| date.format("%a %d %b").to_string());
+            let midday = wttr::hourly_for_hour(day, 12);
+            let desc = midday.and_then(|hourly| hourly.weatherDesc.first()).map_or("N/A", |desc| desc.value.as_str());
+            let code = midday.map_or("", |hourly| hourly.weatherCode.as_str());
+            let icon = wttr::condition_marker(wttr::icon_description(code, desc), options.text_only, options.icons);
+            let temp_span = |temp: &str| match wttr::parse_temp_rounded(temp) {
+                Some(t) => Span::styled(
+                    wttr::format_temp(temp, options.units),
+                    Style::default().fg(wttr::get_temp_color(t, wttr::TempScale::Celsius, options.temp_scheme)),
+                ),
+                None => Span::raw("--"),
+            };
+            Line::from(vec![
+                Span::raw(format!("   {}  ", date)),
+                temp_span(&day.mintempC),
+                Span::raw(" / "),
+                temp_span(&day.maxtempC),
+                Span::raw(format!("  {} {}", icon, truncate_to_width(desc, config::MAX_DESC_WIDTH))),
+            ])
+        })
+        .collect()
+}
+
+pub fn forecast_ui(f: &mut Frame, data: &AppData, options: &config::DisplayOptions, scroll: u16) -> ListExtent {
+    let main_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)])
+        .split(f.size());
+
+    let title_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLACK);
+    let header_widget = Paragraph::new("P185 Forecast - Low / High").style(title_style.bold());
+
+    let mut forecast_text = Vec::new();
+    for region in data.country.regions.iter() {
+        let title = format!("-- {} --", region.title());
+        forecast_text.push(Line::from(Span::styled(title, Style::default().fg(config::CEEFAX_YELLOW).bold())));
+        match data.reports.get(&region.name) {
+            Some(report) if !report.weather.is_empty() => forecast_text.extend(forecast_lines(report, options)),
+            _ => forecast_text.push(Line::from("   No forecast available.")),
+        }
+        forecast_text.push(Line::from(" "));
+    }
+
+    let blue_bg_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLUE);
+    let forecast_block = Block::default();
+    let forecast_widget = Paragraph::new(forecast_text).style(blue_bg_style).scroll((scroll, 0));
+    let extent = ListExtent::of(&forecast_widget, &forecast_block, main_chunks[1]);
+    let forecast_widget = forecast_widget.block(forecast_block);
+
+    let footer_widget = Paragraph::new("[M]ap View").style(blue_bg_style);
+
+    f.render_widget(header_widget, main_chunks[0]);
+    f.render_widget(forecast_widget, main_chunks[1]);
+    f.render_widget(footer_widget, main_chunks[2]);
+    extent
+}
+
+/// Regions with a readable current temperature, warmest first; ties keep the template order.
+fn ranked_regions(data: &AppData) -> Vec<(&config::Region, &wttr::CurrentCondition, i32)> {
+    let mut ranked: Vec<_> = data
+        .country
+        .regions
+        .iter()
+        .filter_map(|region| {
+            let condition = data.reports.get(&region.name)?.current_condition.first()?;
+            Some((region, condition, wttr::parse_temp_rounded(&condition.temp_C)?))
+        })
+        .collect();
+    ranked.sort_by_key(|&(_, _, temp)| std::cmp::Reverse(temp));
+    ranked
+}
+
+pub fn ranking_ui(f: &mut Frame, data: &AppData, options: &config::DisplayOptions, scroll: u16) -> ListExtent {
+    let main_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)])
+        .split(f.size());
+
+    let title_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLACK);
+    let header_widget = Paragraph::new("P186 Temperature Ranking").style(title_style.bold());
+
+    let ranked = ranked_regions(data);
+    let name_width = ranked.iter().map(|(region, _, _)| region.name.width()).max().unwrap_or(0);
+    let mut ranking_text = Vec::new();
+    if ranked.is_empty() {
+        ranking_text.push(Line::from("No temperatures available."));
+    }
+    for (place, (region, condition, temp)) in ranked.iter().enumerate() {
+        let desc = condition.weatherDesc.first().map_or("", |desc| desc.value.as_str());
+        let padding = " ".repeat(name_width - region.name.width());
+        ranking_text.push(Line::from(vec![
+            Span::raw(format!("{:>2}. {}{}  ", place + 1, region.name, padding)),
+            Span::styled(
+                format!("{:>5}", wttr::format_temp(&condition.temp_C, options.units)),
+                Style::default().fg(wttr::get_temp_color(*temp, wttr::TempScale::Celsius, options.temp_scheme)),
+            ),
+            Span::raw(format!("  {}", truncate_to_width(desc, config::MAX_DESC_WIDTH))),
+        ]));
+    }
+
+    let blue_bg_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLUE);
+    let ranking_block = Block::default();
+    let ranking_widget = Paragraph::new(ranking_text).style(blue_bg_style).scroll((scroll, 0));
+    let extent = ListExtent::of(&ranking_widget, &ranking_block, main_chunks[1]);
+    let ranking_widget = ranking_widget.block(ranking_block);
+
+    let footer_widget = Paragraph::new("[M]ap View").style(blue_bg_style);
+
+    f.render_widget(header_widget, main_chunks[0]);
+    f.render_widget(ranking_widget, main_chunks[1]);
+    f.render_widget(footer_widget, main_chunks[2]);
+    extent
+}
+
+pub fn change_log_ui(f: &mut Frame, entries: &VecDeque<String>, scroll: u16) -> ListExtent {
+    let main_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(1), Constraint::Length(1)])
+        .split(f.size());
+
+    let title_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLACK);
+    let header_widget = Paragraph::new("P188 Weather Watch").style(title_style.bold());
+
+    let mut log_text = vec![Line::from(""), Line::styled("Condition changes:", Style::default().fg(config::CEEFAX_YELLOW)), Line::from("")];
+    if entries.is_empty() {
+        log_text.push(Line::from("No changes since the app started."));
+    }
+    log_text.extend(entries.iter().map(|entry| Line::from(entry.clone())));
+
+    let blue_bg_style = Style::default().fg(config::CEEFAX_WHITE).bg(config::CEEFAX_BLUE);
+    let log_block = Block::default().padding(Padding::new(2, 2, 1, 1));
+    let log_widget = Paragraph::new(log_text).style(blue_bg_style).scroll((scroll, 0));
+    let extent = ListExtent::of(&log_widget, &log_block, main_chunks[1]);
+    let log_widget = log_widget.block(log_block);
+
+    let footer_widget = Paragraph::new("[M]ap View").style(blue_bg_style);
+
+    f.render_widget(header_widget, main_chunks[0]);
+    f.render_widget(log_widget, main_chunks[1]);
+    f.render_widget(footer_widget, main_chunks[2]);
+    extent
+}
+
+/// Spaces between the page number and the right-aligned date and time (joined by three spaces)
+/// in a header `width` columns wide; none when the texts alone overflow it.
+fn header_padding(width: u16, left_text: &str, date_text: &str, time_text: &str) -> usize {
+    let used = left_text.width() + date_text.width() + 3 + time_text.width();
+    (width as usize).saturating_sub(used)
 }
 
-fn draw_map_widget<'a>(country: &config::Country, reports: &wttr::WeatherReports) -> Paragraph<'a> {
+/// Today's date for the page header, uppercased like the broadcast pages unless disabled.
+fn header_date(options: &config::DisplayOptions) -> String {
+    let date = Local::now().format("%a %d %b").to_string();
+    if options.uppercase_date {
+        date.to_uppercase()
+    } else {
+        date
+    }
+}
+
+/// Shortens `text` to at most `max_width` terminal columns, ending in an ellipsis when cut.
+pub fn truncate_to_width(text: &str, max_width: usize) -> String {
+    if text.width() <= max_width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if width + char_width + 1 > max_width {
+            break;
+        }
+        truncated.push(c);
+        width += char_width;
+    }
+    truncated.push('…');
+    truncated
+}
+
+/// Maps a 2x2 quadrant bitmask (top-left 1, top-right 2, bottom-left 4, bottom-right 8) to its glyph in `glyphs`.
+fn mosaic_char(glyphs: &[char; 16], bitmask: usize) -> char {
+    glyphs[bitmask & 0xF]
+}
+
+/// Lays out a map temperature label centered on `center_x` and kept inside `map_width` cells.
+/// A column is always reserved for the sign, so digits stay put when a value drops below zero.
+/// Returns the first cell and the label, padded on the left where no sign is shown.
+fn place_temp_label(temp: &str, center_x: usize, map_width: usize) -> (usize, String) {
+    let temp = temp.trim();
+    let digits = temp.trim_start_matches(['-', '+']).chars().count();
+    let label = format!("{:>width$}", temp, width = digits + 1);
+    let width = label.chars().count();
+    let start = center_x.saturating_sub(width / 2).min(map_width.saturating_sub(width));
+    (start, label)
+}
+
+/// Drawn on the map next to the `--home-region` temperature.
+const HOME_MARKER: char = '⌂';
+
+/// Written on the map in place of a temperature for a region with no report.
+const NO_DATA_LABEL: &str = "N/A";
+
+/// Template cells per mosaic pixel that make the whole template fit inside `area`.
+/// Each terminal cell holds 2x2 pixels; below 1.0 the map is enlarged to fill the pane.
+fn fit_map_scale(template: &[String], area: Rect) -> f32 {
+    let width = template.iter().map(|row| row.chars().count()).max().unwrap_or(0);
+    if width == 0 || area.width == 0 || area.height == 0 {
+        return 1.0;
+    }
+    let scale_x = width as f32 / (2.0 * area.width as f32);
+    let scale_y = template.len() as f32 / (2.0 * area.height as f32);
+    scale_x.max(scale_y)
+}
+
+fn draw_map_widget<'a>(
+    country: &config::Country,
+    reports: &wttr::WeatherReports,
+    options: &config::DisplayOptions,
+    scale: f32,
+    show_wind: bool,
+) -> Paragraph<'a> {
     let mut lines: Vec<Line> = Vec::new();
-    let template = &country.map_template;
+    let template: Vec<Vec<char>> = country.map_template.iter().map(|row| row.chars().collect()).collect();
+    let width = template.iter().map(Vec::len).max().unwrap_or(0);
+    // Nearest-cell sampling, so non-integer scales pick the closest template cell.
+    let pixel = |px: usize, py: usize| {
+        let (x, y) = ((px as f32 * scale) as usize, (py as f32 * scale) as usize);
+        template.get(y).and_then(|row| row.get(x)).copied().unwrap_or(' ')
+    };
+    let rows = (template.len() as f32 / (2.0 * scale)).ceil() as usize;
+    let cols = (width as f32 / (2.0 * scale)).ceil() as usize;
 
-    for y in (0..template.len()).step_by(2) {
+    for y in 0..rows {
         let mut spans: Vec<Span> = Vec::new();
-        for x in (0..template[y].len()).step_by(2) {
-            let tl = template[y].chars().nth(x).unwrap_or(' ');
-            let tr = template[y].chars().nth(x + 1).unwrap_or(' ');
-            let bl = if y + 1 < template.len() { template[y + 1].chars().nth(x).unwrap_or(' ') } else { ' ' };
-            let br = if y + 1 < template.len() { template[y + 1].chars().nth(x + 1).unwrap_or(' ') } else { ' ' };
+        for x in 0..cols {
+            let tl = pixel(2 * x, 2 * y);
+            let tr = pixel(2 * x + 1, 2 * y);
+            let bl = pixel(2 * x, 2 * y + 1);
+            let br = pixel(2 * x + 1, 2 * y + 1);
 
             let mut land_pixels = HashMap::new();
             let mut bitmask = 0;
@@ -283,39 +962,97 @@ fn draw_map_widget<'a>(country: &config::Country, reports: &wttr::WeatherReports
 
             let dominant_char = land_pixels.into_iter().max_by_key(|&(_, count)| count).map(|(c, _)| c);
             let mut bg_color = config::CEEFAX_BLUE;
-            if let Some(dc) = dominant_char {
+            if options.outline_only && dominant_char.is_some() {
+                bg_color = config::CEEFAX_GREEN;
+            } else if let Some(dc) = dominant_char {
+                // Land without data, including regions left out by --regions, is drawn neutral.
+                bg_color = config::NO_DATA_GREY;
                 for region in &country.regions {
                     if region.char == dc {
-                        if let Some(report) = reports.get(&region.name) {
-                            let temp = report.current_condition[0].temp_C.parse::<i32>().unwrap_or(0);
-                            bg_color = wttr::get_temp_color(temp);
+                        let temp = reports.get(&region.name)
+                            .and_then(|report| report.current_condition.first())
+                            .and_then(|condition| wttr::parse_temp_rounded(&condition.temp_C));
+                        if let Some(temp) = temp {
+                            bg_color = wttr::get_temp_color(temp, wttr::TempScale::Celsius, options.temp_scheme);
                         }
                         break;
                     }
                 }
             }
             
-            let mosaic_char = config::TELETEXT_CHARS[bitmask];
+            let mosaic_char = mosaic_char(&options.map_glyphs.0, bitmask);
             spans.push(Span::styled(mosaic_char.to_string(), Style::new().bg(bg_color)));
         }
         lines.push(Line::from(spans));
     }
     
-    for region in &country.regions {
-        if let Some(report) = reports.get(&region.name) {
-            let temp_str = &report.current_condition[0].temp_C;
-            let (temp_x, temp_y) = (region.temp_pos[0] / 2, region.temp_pos[1] / 2);
-
-            if (temp_y as usize) < lines.len() {
-                for (i, temp_digit) in temp_str.chars().enumerate() {
-                    let x_pos = (temp_x as usize) + i;
-                    if x_pos < lines[temp_y as usize].spans.len() {
-                        let original_span = &lines[temp_y as usize].spans[x_pos];
-                        let bg_color = original_span.style.bg.unwrap_or(config::CEEFAX_BLUE);
-                        lines[temp_y as usize].spans[x_pos] = Span::styled(
-                            temp_digit.to_string(),
-                            Style::new().fg(config::CEEFAX_WHITE).bold().bg(bg_color),
-                        );
+    // Overwrites one map cell with a glyph, keeping the cell's background; off-map cells are skipped.
+    let overlay = |lines: &mut Vec<Line>, x: usize, y: usize, glyph: char, style: Style| {
+        if let Some(span) = lines.get_mut(y).and_then(|line| line.spans.get_mut(x)) {
+            let bg_color = span.style.bg.unwrap_or(config::CEEFAX_BLUE);
+            *span = Span::styled(glyph.to_string(), style.bg(bg_color));
+        }
+    };
+
+    // Cells already claimed by a temperature label, home marker or wind arrow, per map row.
+    let mut claimed: Vec<(usize, Range<usize>)> = Vec::new();
+    // Whether `cells` on row `y` stay `gap` cells clear of everything claimed so far.
+    let is_free = |claimed: &[(usize, Range<usize>)], y: usize, cells: &Range<usize>, gap: usize| {
+        claimed.iter().all(|(row, other)| *row != y || other.end + gap <= cells.start || cells.end + gap <= other.start)
+    };
+    // The outline shows only the shape, so no region gets an overlay.
+    let overlaid_regions = if options.outline_only { &[][..] } else { &country.regions[..] };
+    for region in overlaid_regions {
+        if let Some(temp_pos) = region.temp_pos {
+            // A region without a report, say because its fetch failed, is marked rather than left blank.
+            let condition = reports.get(&region.name).and_then(|report| report.current_condition.first());
+            let center_x = (temp_pos[0] as f32 / (2.0 * scale)) as usize;
+            let temp_y = (temp_pos[1] as f32 / (2.0 * scale)) as usize;
+            let temp_scale = options.units.scale();
+            let temp = match condition.map(|condition| wttr::parse_temp_rounded(&condition.temp_C)) {
+                Some(Some(celsius)) => temp_scale.convert(celsius).to_string(),
+                // A report without a readable temperature is left blank rather than shown as 0°,
+                // though its home marker and wind arrow are still drawn.
+                Some(None) => String::new(),
+                None => NO_DATA_LABEL.to_string(),
+            };
+            let (start, label) = place_temp_label(&temp, center_x, cols);
+            let cells = start..start + label.chars().count();
+            // `--overlay` only decides the digits; the home marker and wind arrow are always drawn.
+            let show_digits = match options.overlay {
+                config::Overlay::Always => true,
+                // Too narrow on screen for its digits, so leave it to the color alone.
+                config::Overlay::Auto => region.width as f32 / (2.0 * scale) >= cells.len() as f32,
+                config::Overlay::Never => false,
+            };
+            // Keep a one-cell gap so neighbouring labels never run together.
+            if show_digits && is_free(&claimed, temp_y, &cells, 1) {
+                claimed.push((temp_y, cells.clone()));
+
+                for (x, temp_char) in cells.clone().zip(label.chars()) {
+                    if temp_char != ' ' {
+                        overlay(&mut lines, x, temp_y, temp_char, Style::new().fg(config::CEEFAX_WHITE).bold());
+                    }
+                }
+            }
+            if options.home_region.as_deref().is_some_and(|home| home.eq_ignore_ascii_case(&region.name)) {
+                // Above the temperature, or below it on the top row, unless that covers another label.
+                let marker_y = temp_y.checked_sub(1).unwrap_or(temp_y + 1);
+                let marker_cell = cells.end - 1..cells.end;
+                if is_free(&claimed, marker_y, &marker_cell, 0) {
+                    claimed.push((marker_y, marker_cell));
+                    overlay(&mut lines, cells.end - 1, marker_y, HOME_MARKER, Style::new().fg(config::CEEFAX_YELLOW).bold());
+                }
+            }
+            if show_wind {
+                // Calm air, or a direction that isn't a compass point, gets no arrow.
+                if let Some(arrow) = condition.and_then(|condition| wttr::wind_arrow(&condition.winddir16Point)) {
+                    // Just left of the temperature, or just right of it at the map's edge, whether or
+                    // not the temperature itself was written.
+                    let arrow_x = cells.start.checked_sub(1).unwrap_or(cells.end);
+                    if is_free(&claimed, temp_y, &(arrow_x..arrow_x + 1), 0) {
+                        claimed.push((temp_y, arrow_x..arrow_x + 1));
+                        overlay(&mut lines, arrow_x, temp_y, arrow, Style::new().fg(config::CEEFAX_WHITE).dim());
                     }
                 }
             }
@@ -326,3 +1063,364 @@ fn draw_map_widget<'a>(country: &config::Country, reports: &wttr::WeatherReports
         .block(Block::default().style(Style::default().bg(config::CEEFAX_BLUE)))
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mosaic_bitmask_matches_glyph_quadrants() {
+        assert_eq!(mosaic_char(&config::TELETEXT_CHARS, 0), ' ');
+        assert_eq!(mosaic_char(&config::TELETEXT_CHARS, 1), '▘');
+        assert_eq!(mosaic_char(&config::TELETEXT_CHARS, 2), '▝');
+        assert_eq!(mosaic_char(&config::TELETEXT_CHARS, 4), '▖');
+        assert_eq!(mosaic_char(&config::TELETEXT_CHARS, 8), '▗');
+        assert_eq!(mosaic_char(&config::TELETEXT_CHARS, 1 | 2), '▀');
+        assert_eq!(mosaic_char(&config::TELETEXT_CHARS, 4 | 8), '▄');
+        assert_eq!(mosaic_char(&config::TELETEXT_CHARS, 1 | 4), '▌');
+        assert_eq!(mosaic_char(&config::TELETEXT_CHARS, 2 | 8), '▐');
+        assert_eq!(mosaic_char(&config::TELETEXT_CHARS, 15), '█');
+    }
+
+    #[test]
+    fn test_truncate_long_descriptions() {
+        assert_eq!(truncate_to_width("Sunny", 10), "Sunny");
+        assert_eq!(
+            truncate_to_width("Patchy light rain in area with thunder and a very long tail", 20),
+            "Patchy light rain i…"
+        );
+        // Wide characters count as two columns and are never split.
+        assert_eq!(truncate_to_width("晴れ時々曇り", 6), "晴れ…");
+        assert_eq!(truncate_to_width("晴れ時々曇り", 6).width(), 5);
+    }
+
+    #[test]
+    fn test_temp_labels_are_centered_with_room_for_a_sign() {
+        assert_eq!(place_temp_label("5", 10, 40), (9, " 5".to_string()));
+        assert_eq!(place_temp_label("-5", 10, 40), (9, "-5".to_string()));
+        assert_eq!(place_temp_label("-12", 10, 40), (9, "-12".to_string()));
+        assert_eq!(place_temp_label("100", 10, 40), (8, " 100".to_string()));
+        // Pulled back inside the map at either edge.
+        assert_eq!(place_temp_label("-12", 0, 40), (0, "-12".to_string()));
+        assert_eq!(place_temp_label("100", 39, 40), (36, " 100".to_string()));
+    }
+
+    #[test]
+    fn test_mode_style_strips_styling_without_color() {
+        use ratatui::style::Modifier;
+        let style = Style::default().fg(config::CEEFAX_YELLOW).bg(config::CEEFAX_BLUE).add_modifier(Modifier::BOLD);
+        assert_eq!(mode_style(style, config::ColorMode::Truecolor), style);
+        assert_eq!(
+            mode_style(style, config::ColorMode::Ansi16),
+            Style::default().fg(Color::Yellow).bg(Color::Blue).add_modifier(Modifier::BOLD)
+        );
+        assert_eq!(mode_style(style, config::ColorMode::Monochrome), Style::default());
+    }
+
+    #[test]
+    fn test_map_renders_with_custom_glyph_set() {
+        use ratatui::widgets::Widget;
+
+        let country: config::Country = toml::from_str(
+            r#"
+            map_template = ["AA  A", "A    "]
+            regions = [{ name = "Aland", city = "Mariehamn", char = "A" }]
+            "#,
+        )
+        .unwrap();
+        let options = config::DisplayOptions {
+            map_glyphs: config::parse_map_glyphs("0123456789abcdef").unwrap(),
+            ..Default::default()
+        };
+
+        let area = Rect::new(0, 0, 3, 1);
+        let mut buffer = ratatui::buffer::Buffer::empty(area);
+        draw_map_widget(&country, &HashMap::new(), &options, 1.0, false).render(area, &mut buffer);
+        let row: String = (0..3).map(|x| buffer.get(x, 0).symbol()).collect();
+        assert_eq!(row, "701");
+    }
+
+    #[test]
+    fn test_forecast_lines_for_each_day() {
+        let hourly = |time: &str, code: &str, desc: &str| {
+            format!(r#"{{"time": "{}", "tempC": "10", "DewPointC": "5", "weatherCode": "{}", "weatherDesc": [{{"value": "{}"}}]}}"#, time, code, desc)
+        };
+        let body = format!(
+            r#"{{"current_condition": [], "weather": [
+                {{"date": "2024-06-01", "maxtempC": "21", "mintempC": "9", "hourly": [{}, {}]}},
+                {{"date": "2024-06-02", "maxtempC": "14", "mintempC": "-2", "hourly": [{}]}},
+                {{"date": "2024-06-03", "hourly": []}}
+            ]}}"#,
+            hourly("0", "113", "Clear"),
+            hourly("1200", "296", "Light rain"),
+            hourly("1200", "338", "Heavy snow"),
+        );
+        let report = wttr::parse_report(&body).unwrap();
+        let text = |options: &config::DisplayOptions| -> Vec<String> {
+            forecast_lines(&report, options).iter().map(|line| line.spans.iter().map(|span| span.content.as_ref()).collect()).collect()
+        };
+
+        assert_eq!(
+            text(&config::DisplayOptions::default()),
+            vec![
+                "   Sat 01 Jun  9°C / 21°C  🌦️ Light rain",
+                "   Sun 02 Jun  -2°C / 14°C  ❄️ Heavy snow",
+                "   Mon 03 Jun  -- / --  ? N/A",
+            ]
+        );
+        let imperial = config::DisplayOptions { units: config::Units::Imperial, ..Default::default() };
+        assert!(text(&imperial)[0].contains("48°F / 70°F"));
+    }
+
+    #[test]
+    fn test_detail_lines_survive_a_truncated_report() {
+        let options = config::DisplayOptions::default();
+        let empty = wttr::parse_report(r#"{"current_condition": [], "weather": []}"#).unwrap();
+        let text: Vec<String> = region_detail_lines(&empty, &options, None, None).iter().map(|line| line.to_string()).collect();
+        assert_eq!(text, vec!["   --"]);
+
+        let no_desc = wttr::parse_report(
+            r#"{"current_condition": [{"temp_C": "9", "FeelsLikeC": "7", "windspeedKmph": "20", "winddir16Point": "SW", "precipMM": "0.2", "weatherDesc": []}],
+                "weather": [{"date": "2024-06-01", "hourly": []}]}"#,
+        )
+        .unwrap();
+        let text: Vec<String> = region_detail_lines(&no_desc, &options, None, Some('↑')).iter().map(|line| line.to_string()).collect();
+        assert_eq!(text[0], "   ? --");
+        assert!(text.contains(&"   Today: Unknown".to_string()));
+    }
+
+    #[test]
+    fn test_text_only_band_word_follows_the_current_scheme() {
+        let summary = Summary {
+            region: "Wales".to_string(),
+            text: "Wales: Sunny".to_string(),
+            temp_c: Some("18".to_string()),
+            icon: "",
+            color: config::CEEFAX_WHITE,
+        };
+        let mut options = config::DisplayOptions { text_only: true, ..Default::default() };
+        options.temp_scheme = wttr::TempScheme::Fine;
+        assert_eq!(summary_text(&summary, &options), "Wales: Sunny (WARM 18°C)");
+        options.temp_scheme = wttr::TempScheme::Seasonal;
+        assert_eq!(summary_text(&summary, &options), "Wales: Sunny (MILD 18°C)");
+        options.text_only = false;
+        assert_eq!(summary_text(&summary, &options), "Wales: Sunny");
+    }
+
+    #[test]
+    fn test_pinned_summary_moves_to_top() {
+        let summary = |region: &str| Summary {
+            region: region.to_string(),
+            text: String::new(),
+            temp_c: None,
+            icon: "",
+            color: config::CEEFAX_WHITE,
+        };
+        let summaries = vec![summary("S. England"), summary("Wales"), summary("Scotland")];
+        let order = |pinned| -> Vec<&str> {
+            ordered_summaries(&summaries, pinned).iter().map(|s| s.region.as_str()).collect()
+        };
+
+        assert_eq!(order(Some("scotland")), vec!["Scotland", "S. England", "Wales"]);
+        assert_eq!(order(None), vec!["S. England", "Wales", "Scotland"]);
+        assert_eq!(order(Some("Atlantis")), vec!["S. England", "Wales", "Scotland"]);
+    }
+
+    /// Two regions side by side, two cells tall: `Wide`, six template columns across, and `Edge`, two.
+    fn two_region_country() -> config::Country {
+        config::parse_country(
+            r#"
+            map_template = ["WWWWWW      EE  ", "WWWWWW      EE  ", "WWWWWW      EE  ", "WWWWWW      EE  "]
+            regions = [
+                { name = "Wide", city = "Wideford", char = "W", temp_pos = [2, 0] },
+                { name = "Edge", city = "Edgeley", char = "E", temp_pos = [12, 0] },
+            ]
+            "#,
+        )
+        .unwrap()
+    }
+
+    /// A clear report at `temp` with a northerly wind.
+    fn report(temp: &str) -> wttr::WeatherReport {
+        wttr::parse_report(&wttr::MockWeatherClient::body(temp, "N", "Clear")).unwrap()
+    }
+
+    /// Draws `country` at full scale into an 8x2 buffer.
+    fn render_map(
+        country: &config::Country,
+        reports: &HashMap<String, wttr::WeatherReport>,
+        options: &config::DisplayOptions,
+        show_wind: bool,
+    ) -> ratatui::buffer::Buffer {
+        use ratatui::widgets::Widget;
+
+        let area = Rect::new(0, 0, 8, 2);
+        let mut buffer = ratatui::buffer::Buffer::empty(area);
+        draw_map_widget(country, reports, options, 1.0, show_wind).render(area, &mut buffer);
+        buffer
+    }
+
+    fn row(buffer: &ratatui::buffer::Buffer) -> String {
+        (0..buffer.area.width).map(|x| buffer.get(x, 0).symbol()).collect()
+    }
+
+    #[test]
+    fn test_auto_overlay_skips_regions_too_narrow_for_digits() {
+        let country = two_region_country();
+        let reports = HashMap::from([("Wide".to_string(), report("12")), ("Edge".to_string(), report("7"))]);
+        let render = |overlay| row(&render_map(&country, &reports, &config::DisplayOptions { overlay, ..Default::default() }, false));
+
+        // Edge is one cell wide, too narrow for " 7".
+        assert_eq!(render(config::Overlay::Auto), "█12   █ ");
+        assert_eq!(render(config::Overlay::Always), "█12   7 ");
+        assert_eq!(render(config::Overlay::Never), "███   █ ");
+    }
+
+    #[test]
+    fn test_hidden_temperatures_keep_their_wind_arrow_and_home_marker() {
+        let country = two_region_country();
+        let reports = HashMap::from([("Wide".to_string(), report("12")), ("Edge".to_string(), report("7"))]);
+        let arrow = wttr::wind_arrow("N").unwrap().to_string();
+        for overlay in [config::Overlay::Never, config::Overlay::Auto] {
+            let options = config::DisplayOptions { overlay, home_region: Some("Edge".to_string()), ..Default::default() };
+            let buffer = render_map(&country, &reports, &options, true);
+
+            // Edge's " 7" would span cells 5 and 6: the arrow sits left of it and the marker below.
+            assert_eq!(buffer.get(4, 0).symbol(), arrow, "{:?}", overlay);
+            assert_eq!(buffer.get(6, 1).symbol(), HOME_MARKER.to_string(), "{:?}", overlay);
+            assert_eq!(buffer.get(6, 0).symbol(), "█", "{:?}", overlay);
+        }
+    }
+
+    #[test]
+    fn test_crowded_labels_keep_their_wind_arrow() {
+        let country = config::parse_country(
+            r#"
+            map_template = ["WWWWWWEEEEEE", "WWWWWWEEEEEE"]
+            regions = [
+                { name = "East", city = "Easton", char = "E", temp_pos = [8, 0] },
+                { name = "West", city = "Weston", char = "W", temp_pos = [4, 0] },
+            ]
+            "#,
+        )
+        .unwrap();
+        let reports = HashMap::from([("East".to_string(), report("1")), ("West".to_string(), report("2"))]);
+        let buffer = render_map(&country, &reports, &config::DisplayOptions::default(), true);
+
+        // West's " 2" would run into East's " 1", so only West's arrow is written, clear of East's.
+        let arrow = wttr::wind_arrow("N").unwrap();
+        assert_eq!(row(&buffer), format!("{0}█{0}█1█  ", arrow));
+    }
+
+    #[test]
+    fn test_home_marker_never_covers_a_neighbouring_label() {
+        let country = config::parse_country(
+            r#"
+            map_template = ["NNNNNNNN", "NNNNNNNN", "SSSSSSSS", "SSSSSSSS"]
+            regions = [
+                { name = "South", city = "Southsea", char = "S", temp_pos = [4, 2] },
+                { name = "North", city = "Northam", char = "N", temp_pos = [4, 0] },
+            ]
+            "#,
+        )
+        .unwrap();
+        let reports = HashMap::from([("North".to_string(), report("1")), ("South".to_string(), report("2"))]);
+        let options = config::DisplayOptions { home_region: Some("North".to_string()), ..Default::default() };
+        let buffer = render_map(&country, &reports, &options, false);
+
+        // North's marker would go below its " 1", onto the "2" of South's label.
+        assert_eq!(buffer.get(2, 0).symbol(), "1");
+        assert_eq!(buffer.get(2, 1).symbol(), "2");
+    }
+
+    #[test]
+    fn test_map_rounds_decimal_temps_and_leaves_unreadable_ones_blank() {
+        let country = two_region_country();
+        let reports = HashMap::from([("Wide".to_string(), report("-4.6")), ("Edge".to_string(), report("N/A"))]);
+        let options = config::DisplayOptions { overlay: config::Overlay::Always, ..Default::default() };
+        let buffer = render_map(&country, &reports, &options, false);
+
+        assert_eq!(row(&buffer), "-5█   █ ");
+        let cold = wttr::get_temp_color(-5, wttr::TempScale::Celsius, options.temp_scheme);
+        assert_eq!(buffer.get(0, 0).bg, cold);
+        assert_eq!(buffer.get(6, 0).bg, config::NO_DATA_GREY);
+
+        let buffer = render_map(&country, &reports, &options, true);
+        assert_eq!(buffer.get(5, 0).symbol(), wttr::wind_arrow("N").unwrap().to_string());
+    }
+
+    #[test]
+    fn test_widest_map_fits_an_80_column_terminal() {
+        // At 80x24 the map pane is 55% of the width and sits under the 5-row title,
+        // between the 1-row header and the 2-row footer.
+        let pane = Rect::new(0, 0, 80 * 55 / 100, 24 - 1 - 2 - 5);
+        let usa = config::load_country_file(&std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("templates/usa.toml")).unwrap();
+        assert!(fit_map_scale(&usa.map_template, pane) <= 1.0);
+    }
+
+    #[test]
+    fn test_header_padding_never_underflows() {
+        assert_eq!(header_padding(80, "P181 CEEFAX 181", "Sat 01 Jun", "12:00/00"), 80 - 15 - 10 - 3 - 8);
+        assert_eq!(header_padding(10, "P181 CEEFAX 181", "Sat 01 Jun", "12:00/00"), 0);
+        assert_eq!(header_padding(0, "", "", ""), 0);
+        // Measured in columns, so an accented month doesn't cost an extra space.
+        assert_eq!(header_padding(40, "P181", "Mär 01", "12:00/00"), 40 - 4 - 6 - 3 - 8);
+    }
+
+    #[test]
+    fn test_list_extent_counts_wrapped_rows() {
+        let area = Rect::new(0, 0, 12, 8);
+        let lines = vec![Line::from(""), Line::from("12345"), Line::from("one two three four five six")];
+        // Wrapped at word boundaries like the widget draws it: "one two", "three four", "five six".
+        let wrapped = Paragraph::new(lines.clone()).wrap(Wrap { trim: true });
+        assert_eq!(ListExtent::of(&wrapped, &Block::default(), area), ListExtent { rows: 1 + 1 + 3, visible: 8 });
+        let padded = Block::default().padding(Padding::new(2, 2, 1, 1));
+        assert_eq!(ListExtent::of(&wrapped, &padded, area), ListExtent { rows: 1 + 1 + 4, visible: 6 });
+        assert_eq!(ListExtent::of(&Paragraph::new(lines), &padded, area), ListExtent { rows: 3, visible: 6 });
+        assert_eq!(ListExtent { rows: 30, visible: 10 }.max_scroll(), 20);
+        assert_eq!(ListExtent { rows: 3, visible: 10 }.max_scroll(), 0);
+    }
+
+    #[test]
+    fn test_refresh_countdown_formatting() {
+        assert_eq!(format_refresh_countdown(Duration::ZERO), "0:00");
+        assert_eq!(format_refresh_countdown(Duration::from_millis(200)), "0:01");
+        assert_eq!(format_refresh_countdown(Duration::from_secs(65)), "1:05");
+        assert_eq!(format_refresh_countdown(Duration::from_secs(15 * 60)), "15:00");
+        assert_eq!(format_refresh_countdown(Duration::from_secs(15 * 60).saturating_sub(Duration::from_secs(20 * 60))), "0:00");
+    }
+
+    #[test]
+    fn test_scroll_follows_the_selection() {
+        assert_eq!(scroll_to_show(0, 10), 0);
+        assert_eq!(scroll_to_show(9, 10), 0);
+        assert_eq!(scroll_to_show(10, 10), 1);
+        assert_eq!(scroll_to_show(25, 10), 16);
+        assert_eq!(scroll_to_show(3, 0), 3);
+    }
+
+    #[test]
+    fn test_unselectable_note_names_items_past_nine() {
+        assert_eq!(unselectable_note(9), "");
+        assert_eq!(unselectable_note(10), "  (10 not selectable)");
+        assert_eq!(unselectable_note(12), "  (10-12 not selectable)");
+    }
+
+    #[test]
+    fn test_revealed_rows_grow_to_the_full_page() {
+        assert_eq!(revealed_rows(24, Duration::ZERO), 0);
+        assert_eq!(revealed_rows(24, config::REVEAL_DURATION / 2), 12);
+        assert_eq!(revealed_rows(24, config::REVEAL_DURATION * 3), 24);
+    }
+
+    #[test]
+    fn test_fit_map_scale_fits_the_larger_dimension() {
+        let template = vec!["X".repeat(80); 40];
+        // 40x20 cells hold 80x40 pixels exactly.
+        assert_eq!(fit_map_scale(&template, Rect::new(0, 0, 40, 20)), 1.0);
+        // Height is the constraint: 40 rows into 10 cells of 2 pixels.
+        assert_eq!(fit_map_scale(&template, Rect::new(0, 0, 40, 10)), 2.0);
+        // A roomy pane enlarges the map.
+        assert_eq!(fit_map_scale(&template, Rect::new(0, 0, 80, 80)), 0.5);
+        assert_eq!(fit_map_scale(&template, Rect::new(0, 0, 0, 0)), 1.0);
+    }
+}
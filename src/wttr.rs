@@ -1,7 +1,8 @@
-use crate::config;
+use crate::{cache::DiskCache, config};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 use ratatui::style::Color;
 use serde::Deserialize;
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 pub type WeatherReports = HashMap<String, WeatherReport>;
 
@@ -10,14 +11,46 @@ pub struct WeatherDesc {
     pub value: String,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, Default)]
 #[allow(non_snake_case)]
 pub struct Hourly {
     pub time: String,
     pub tempC: String,
+    pub DewPointC: String,
+    #[serde(default)]
+    pub uvIndex: String,
+    /// WWO condition code, e.g. "113" for sunny; empty when the provider doesn't send one.
+    #[serde(default)]
+    pub weatherCode: String,
     pub weatherDesc: Vec<WeatherDesc>,
 }
 
+impl Hourly {
+    /// Minutes past midnight at which this slot starts, whatever the feed's time format.
+    pub fn minutes(&self) -> Option<u32> {
+        slot_minutes(&self.time)
+    }
+}
+
+/// Normalizes an hourly `time` to minutes past midnight. wttr.in sends HHMM without padding
+/// ("0", "300", "1500") but "H:MM" clock strings are accepted too; hourly and 3-hourly feeds alike.
+pub fn slot_minutes(time: &str) -> Option<u32> {
+    let time = time.trim();
+    let minutes = match time.split_once(':') {
+        Some((hours, minutes)) => hours.parse::<u32>().ok()? * 60 + minutes.parse::<u32>().ok().filter(|m| *m < 60)?,
+        None => {
+            let hhmm = time.parse::<u32>().ok()?;
+            (hhmm % 100 < 60).then_some(hhmm / 100 * 60 + hhmm % 100)?
+        }
+    };
+    (minutes < 24 * 60).then_some(minutes)
+}
+
+/// Formats minutes past midnight as an "HH:MM" label.
+pub fn format_slot_time(minutes: u32) -> String {
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
 #[derive(Deserialize, Debug, Clone)]
 #[allow(non_snake_case)]
 pub struct CurrentCondition {
@@ -26,11 +59,43 @@ pub struct CurrentCondition {
     pub windspeedKmph: String,
     pub winddir16Point: String,
     pub precipMM: String,
+    /// Relative humidity as a percentage, e.g. "82".
+    #[serde(default)]
+    pub humidity: String,
+    #[serde(default)]
+    pub uvIndex: String,
+    /// Sea-level pressure in millibars, e.g. "1016".
+    #[serde(default)]
+    pub pressure: String,
+    /// WWO condition code, e.g. "113" for sunny; empty when the provider doesn't send one.
+    #[serde(default)]
+    pub weatherCode: String,
     pub weatherDesc: Vec<WeatherDesc>,
 }
 
-#[derive(Deserialize, Debug, Clone)]
+/// Sun times for a day, as local clock strings like "05:12 AM".
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct Astronomy {
+    pub sunrise: String,
+    pub sunset: String,
+    /// A named phase such as "Waxing Gibbous".
+    #[serde(default)]
+    pub moon_phase: String,
+    /// Percentage of the disc lit, e.g. "48".
+    #[serde(default)]
+    pub moon_illumination: String,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[allow(non_snake_case)]
 pub struct WeatherDay {
+    pub date: String,
+    #[serde(default)]
+    pub maxtempC: String,
+    #[serde(default)]
+    pub mintempC: String,
+    #[serde(default)]
+    pub astronomy: Vec<Astronomy>,
     pub hourly: Vec<Hourly>,
 }
 
@@ -43,99 +108,1025 @@ pub struct WeatherReport {
 /// The trait that defines our contract for any weather data provider.
 pub trait WeatherClient: Send + Sync + 'static {
     fn fetch(&self, city: &str) -> Result<WeatherReport, String>;
+
+    /// The data source shown in the attribution footer.
+    fn provider_name(&self) -> &'static str;
+
+    /// Returns a still-fresh cached report for `city` and its age, if the client keeps one.
+    fn cached(&self, _city: &str) -> Option<(WeatherReport, Duration)> {
+        None
+    }
+
+    /// Fetches every city at once, in order, for clients that can do so concurrently.
+    /// `None` means the caller should fetch one city at a time.
+    fn fetch_many(&self, _cities: &[&str]) -> Option<Vec<Result<WeatherReport, String>>> {
+        None
+    }
+
+    /// Fetches `city` from the network even if the cache still holds a fresh report.
+    fn fetch_fresh(&self, city: &str) -> Result<WeatherReport, String> {
+        self.fetch(city)
+    }
+
+    /// `fetch_many`, skipping the cache like `fetch_fresh`.
+    fn fetch_many_fresh(&self, _cities: &[&str]) -> Option<Vec<Result<WeatherReport, String>>> {
+        None
+    }
+}
+
+/// Wraps a client so every fetch skips its cache, for refreshes the user asked for.
+pub struct Uncached(pub Arc<dyn WeatherClient>);
+
+impl WeatherClient for Uncached {
+    fn fetch(&self, city: &str) -> Result<WeatherReport, String> {
+        self.0.fetch_fresh(city)
+    }
+
+    fn provider_name(&self) -> &'static str {
+        self.0.provider_name()
+    }
+
+    fn fetch_many(&self, cities: &[&str]) -> Option<Vec<Result<WeatherReport, String>>> {
+        self.0.fetch_many_fresh(cities)
+    }
+}
+
+/// Tries each query in turn until one returns current conditions.
+/// Returns `Ok(None)` if every query answered without conditions, or the last error if the final query failed.
+pub fn fetch_first_valid(client: &dyn WeatherClient, queries: &[&str]) -> Result<Option<WeatherReport>, String> {
+    let mut last = Ok(None);
+    for query in queries {
+        match client.fetch(query) {
+            Ok(report) if !report.current_condition.is_empty() => return Ok(Some(report)),
+            Ok(_) => last = Ok(None),
+            Err(e) => last = Err(e),
+        }
+    }
+    last
+}
+
+/// Combines the reports for a region's cities, the main city first, into the one shown for the region.
+/// Reports without a readable current temperature are ignored unless none have one.
+pub fn aggregate_reports(mut reports: Vec<WeatherReport>, aggregation: config::Aggregation) -> Option<WeatherReport> {
+    let current = |report: &WeatherReport, feels_like: bool| {
+        let condition = report.current_condition.first()?;
+        let value = if feels_like { &condition.FeelsLikeC } else { &condition.temp_C };
+        parse_temp_rounded(value)
+    };
+    let readable: Vec<usize> = (0..reports.len()).filter(|&i| current(&reports[i], false).is_some()).collect();
+    if readable.len() < 2 {
+        let index = readable.first().copied().unwrap_or(0);
+        return (index < reports.len()).then(|| reports.swap_remove(index));
+    }
+
+    match aggregation {
+        config::Aggregation::Representative => {
+            let mut by_temp = readable;
+            by_temp.sort_by_key(|&i| current(&reports[i], false));
+            Some(reports.swap_remove(by_temp[(by_temp.len() - 1) / 2]))
+        }
+        config::Aggregation::Average => {
+            let average = |feels_like: bool| {
+                let temps: Vec<i32> = readable.iter().filter_map(|&i| current(&reports[i], feels_like)).collect();
+                (!temps.is_empty()).then(|| (temps.iter().sum::<i32>() as f64 / temps.len() as f64).round().to_string())
+            };
+            let (temp, feels_like) = (average(false), average(true));
+            let mut report = reports.swap_remove(readable[0]);
+            let condition = &mut report.current_condition[0];
+            condition.temp_C = temp.unwrap_or_default();
+            if let Some(feels_like) = feels_like {
+                condition.FeelsLikeC = feels_like;
+            }
+            Some(report)
+        }
+    }
+}
+
+/// A failed fetch attempt. Only `Transient` ones, such as network errors, are worth repeating.
+pub enum FetchFailure {
+    Transient(String),
+    Permanent(String),
+}
+
+/// The retry schedule shared by the blocking and async clients: up to `retries` more attempts,
+/// waiting from `RETRY_BASE_DELAY` and doubling after each transient failure.
+pub struct Backoff {
+    delay: Duration,
+    retries_left: u32,
+}
+
+impl Backoff {
+    pub fn new(retries: u32) -> Self {
+        Self { delay: config::RETRY_BASE_DELAY, retries_left: retries }
+    }
+
+    /// How long to wait before retrying after `failure`, or its error if it shouldn't be retried.
+    pub fn after(&mut self, failure: FetchFailure) -> Result<Duration, String> {
+        match failure {
+            FetchFailure::Transient(_) if self.retries_left > 0 => {
+                let delay = self.delay;
+                self.delay *= 2;
+                self.retries_left -= 1;
+                Ok(delay)
+            }
+            FetchFailure::Transient(e) | FetchFailure::Permanent(e) => Err(e),
+        }
+    }
+}
+
+/// Makes up to `retries + 1` attempts, calling `sleep` with an exponentially growing delay
+/// after each transient failure. A permanent failure is returned straight away.
+pub fn retry_with_backoff<T>(
+    retries: u32,
+    mut sleep: impl FnMut(Duration),
+    mut attempt: impl FnMut() -> Result<T, FetchFailure>,
+) -> Result<T, String> {
+    let mut backoff = Backoff::new(retries);
+    loop {
+        match attempt() {
+            Ok(value) => return Ok(value),
+            Err(failure) => sleep(backoff.after(failure)?),
+        }
+    }
 }
 
 /// The implementation that makes real network calls to wttr.in.
 pub struct LiveWeatherClient {
     client: reqwest::blocking::Client,
+    cache: Option<DiskCache>,
+    options: config::FetchOptions,
 }
 
 impl LiveWeatherClient {
-    pub fn new() -> Self {
-        Self {
-            client: reqwest::blocking::Client::new(),
+    pub fn new(cache: Option<DiskCache>, options: &config::FetchOptions) -> Result<Self, String> {
+        let mut builder = reqwest::blocking::Client::builder().timeout(options.timeout);
+        if let Some(url) = &options.proxy {
+            let proxy = reqwest::Proxy::all(url).map_err(|e| format!("Invalid proxy URL '{}': {}", url, e))?;
+            builder = builder.proxy(proxy);
         }
+        let client = builder
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        Ok(Self {
+            client,
+            cache,
+            options: options.clone(),
+        })
     }
-}
 
-impl WeatherClient for LiveWeatherClient {
-    fn fetch(&self, city: &str) -> Result<WeatherReport, String> {
-        let url = format!("https://wttr.in/{}?format=j1", city);
+    /// Downloads the raw j1 response body for a city.
+    fn request(&self, city: &str) -> Result<String, FetchFailure> {
+        let url = format!("{}?format=j1", location_url(city));
         let response = self
             .client
             .get(url)
             .send()
-            .map_err(|e| format!("Network request failed: {}", e))?;
-
-        let text = response
-            .text()
-            .map_err(|e| format!("Failed to read response body: {}", e))?;
-
-        match serde_json::from_str::<WeatherReport>(&text) {
-            Ok(report) => Ok(report),
-            Err(e) => {
-                let pretty_payload = match serde_json::from_str::<serde_json::Value>(&text) {
-                    Ok(v) => serde_json::to_string_pretty(&v).unwrap_or_else(|_| text.clone()),
-                    Err(_) => text,
-                };
-                Err(format!(
-                    "Failed to decode API response: {}\n\n-- API Payload --\n{}",
-                    e, pretty_payload
-                ))
-            }
+            .map_err(|e| FetchFailure::Transient(request_error(e, &self.options)))?;
+        if !response.status().is_success() {
+            return Err(status_failure(response.status()));
         }
+
+        response.text().map_err(|e| FetchFailure::Transient(body_error(e, &self.options)))
     }
 }
 
-pub fn get_temp_color(temp: i32) -> Color {
-    match temp {
-        t if t < 10 => config::CEEFAX_GREEN,
-        t if (10..15).contains(&t) => config::CEEFAX_CYAN,
-        _ => config::CEEFAX_YELLOW,
+impl WeatherClient for LiveWeatherClient {
+    fn fetch(&self, city: &str) -> Result<WeatherReport, String> {
+        match self.cached(city) {
+            Some((report, _)) => Ok(report),
+            None => self.fetch_fresh(city),
+        }
     }
+
+    fn fetch_fresh(&self, city: &str) -> Result<WeatherReport, String> {
+        // A response that won't decode won't improve on a retry, so it isn't repeated.
+        let (report, text) = retry_with_backoff(self.options.retries, std::thread::sleep, || {
+            let text = self.request(city)?;
+            let report = parse_report(&text).map_err(FetchFailure::Permanent)?;
+            Ok((report, text))
+        })?;
+        if let Some(cache) = &self.cache {
+            // A failed cache write only costs us a refetch next time.
+            let _ = cache.store(city, &text);
+        }
+        Ok(report)
+    }
+
+    fn cached(&self, city: &str) -> Option<(WeatherReport, Duration)> {
+        let (body, age) = self.cache.as_ref()?.load(city)?;
+        parse_report(&body).ok().map(|report| (report, age))
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "wttr.in"
+    }
+}
+
+/// Describes a failed request, calling out a timeout or a proxy that couldn't be reached.
+pub fn request_error(e: reqwest::Error, options: &config::FetchOptions) -> String {
+    if e.is_timeout() {
+        return timeout_error(options);
+    }
+    match &options.proxy {
+        Some(proxy) if e.is_connect() => format!("Could not connect through proxy {}: {}", proxy, e),
+        _ => format!("Network request failed: {}", e),
+    }
+}
+
+/// Fails an attempt the server answered with an error status. A 4xx, such as a bad query,
+/// won't change on a retry, while a 5xx may be gone by the next attempt.
+pub fn status_failure(status: reqwest::StatusCode) -> FetchFailure {
+    let message = format!("Server answered {}", status);
+    if status.is_client_error() {
+        FetchFailure::Permanent(message)
+    } else {
+        FetchFailure::Transient(message)
+    }
+}
+
+/// Describes a failure while reading a response body.
+pub fn body_error(e: reqwest::Error, options: &config::FetchOptions) -> String {
+    if e.is_timeout() {
+        return timeout_error(options);
+    }
+    format!("Failed to read response body: {}", e)
+}
+
+fn timeout_error(options: &config::FetchOptions) -> String {
+    format!("Request timed out after {}s", options.timeout.as_secs_f32())
 }
 
-/// Maps a weather description string to a Unicode symbol string slice.
-pub fn get_weather_icon(description: &str) -> &'static str {
+/// Percent-encodes a city name for use as a wttr.in path segment.
+pub fn encode_location(city: &str) -> String {
+    let mut encoded = String::new();
+    for byte in city.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// The browser-friendly wttr.in page for a city.
+pub fn location_url(city: &str) -> String {
+    format!("https://wttr.in/{}", encode_location(city))
+}
+
+/// Decodes a j1 response body, turning wttr.in's HTML "busy" pages into a friendly error.
+pub fn parse_report(text: &str) -> Result<WeatherReport, String> {
+    if text.trim_start().starts_with('<') {
+        return Err("wttr.in is busy, try again shortly.".to_string());
+    }
+
+    match serde_json::from_str::<WeatherReport>(text) {
+        Ok(report) => Ok(report),
+        Err(e) => {
+            let pretty_payload = match serde_json::from_str::<serde_json::Value>(text) {
+                Ok(v) => serde_json::to_string_pretty(&v).unwrap_or_else(|_| text.to_string()),
+                Err(_) => text.to_string(),
+            };
+            Err(format!(
+                "Failed to decode API response: {}\n\n-- API Payload --\n{}",
+                e, pretty_payload
+            ))
+        }
+    }
+}
+
+/// A map temperature band: everything below `upper` (°C) not claimed by an earlier band.
+pub struct TempBand {
+    pub upper: i32,
+    pub color: Color,
+    pub word: &'static str,
+}
+
+pub const TEMP_BANDS: [TempBand; 3] = [
+    TempBand { upper: 10, color: config::CEEFAX_GREEN, word: "COLD" },
+    TempBand { upper: 15, color: config::CEEFAX_CYAN, word: "MILD" },
+    TempBand { upper: i32::MAX, color: config::CEEFAX_YELLOW, word: "WARM" },
+];
+
+const FINE_TEMP_BANDS: [TempBand; 5] = [
+    TempBand { upper: 0, color: config::CEEFAX_WHITE, word: "FREEZING" },
+    TempBand { upper: 10, color: config::CEEFAX_GREEN, word: "COLD" },
+    TempBand { upper: 15, color: config::CEEFAX_CYAN, word: "MILD" },
+    TempBand { upper: 20, color: config::CEEFAX_YELLOW, word: "WARM" },
+    TempBand { upper: i32::MAX, color: config::CEEFAX_RED, word: "HOT" },
+];
+
+const SEASONAL_TEMP_BANDS: [TempBand; 4] = [
+    TempBand { upper: 15, color: config::CEEFAX_GREEN, word: "COOL" },
+    TempBand { upper: 22, color: config::CEEFAX_CYAN, word: "MILD" },
+    TempBand { upper: 28, color: config::CEEFAX_YELLOW, word: "WARM" },
+    TempBand { upper: i32::MAX, color: config::CEEFAX_RED, word: "HOT" },
+];
+
+/// The preset temperature band schemes that can be cycled through at runtime.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TempScheme {
+    #[default]
+    Coarse,
+    Fine,
+    Seasonal,
+}
+
+impl TempScheme {
+    pub fn bands(self) -> &'static [TempBand] {
+        match self {
+            TempScheme::Coarse => &TEMP_BANDS,
+            TempScheme::Fine => &FINE_TEMP_BANDS,
+            TempScheme::Seasonal => &SEASONAL_TEMP_BANDS,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            TempScheme::Coarse => TempScheme::Fine,
+            TempScheme::Fine => TempScheme::Seasonal,
+            TempScheme::Seasonal => TempScheme::Coarse,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            TempScheme::Coarse => "COARSE",
+            TempScheme::Fine => "FINE",
+            TempScheme::Seasonal => "SEASONAL",
+        }
+    }
+
+}
+
+/// The scale a temperature is expressed in; band thresholds are converted to match.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum TempScale {
+    #[default]
+    Celsius,
+    Fahrenheit,
+}
+
+impl TempScale {
+    /// Converts a Celsius reading, or band threshold, into this scale.
+    pub fn convert(self, celsius: i32) -> i32 {
+        match (self, celsius) {
+            (_, i32::MAX) | (TempScale::Celsius, _) => celsius,
+            (TempScale::Fahrenheit, _) => celsius_to_fahrenheit(celsius),
+        }
+    }
+
+    pub fn symbol(self) -> &'static str {
+        match self {
+            TempScale::Celsius => "°C",
+            TempScale::Fahrenheit => "°F",
+        }
+    }
+}
+
+fn band_index(temp: i32, scale: TempScale, bands: &[TempBand]) -> usize {
+    bands.iter().position(|band| temp < scale.convert(band.upper)).unwrap_or(bands.len() - 1)
+}
+
+/// The band a temperature in `scale` falls into.
+pub fn get_temp_band(temp: i32, scale: TempScale, scheme: TempScheme) -> &'static TempBand {
+    let bands = scheme.bands();
+    &bands[band_index(temp, scale, bands)]
+}
+
+pub fn get_temp_color(temp: i32, scale: TempScale, scheme: TempScheme) -> Color {
+    get_temp_band(temp, scale, scheme).color
+}
+
+/// The map key entry for band `index`, such as "Below 10°" or "50-59°".
+pub fn band_label(scheme: TempScheme, index: usize, scale: TempScale) -> String {
+    let bands = scheme.bands();
+    let lower = index.checked_sub(1).map(|i| scale.convert(bands[i].upper));
+    match (lower, bands[index].upper) {
+        (None, upper) => format!("Below {}°", scale.convert(upper)),
+        (Some(lower), i32::MAX) => format!("Above {}°", lower),
+        (Some(lower), upper) => format!("{}-{}°", lower, scale.convert(upper)),
+    }
+}
+
+/// Explains which band, and so which map color, a temperature in `scale` falls into.
+pub fn explain_temp_color(temp: i32, scale: TempScale, scheme: TempScheme) -> String {
+    let bands = scheme.bands();
+    let index = band_index(temp, scale, bands);
+    let band = &bands[index];
+    let color = config::CEEFAX_PALETTE
+        .iter()
+        .find(|(_, color)| *color == band.color)
+        .map_or("?", |(name, _)| name);
+    let reason = match (index.checked_sub(1).map(|i| scale.convert(bands[i].upper)), band.upper) {
+        (None, upper) => format!("is below the {}° threshold", scale.convert(upper)),
+        (Some(lower), i32::MAX) => format!("is at or above the {}° threshold", lower),
+        (Some(lower), upper) => format!("is between the {}° and {}° thresholds", lower, scale.convert(upper)),
+    };
+    format!("{}: {}{} {}, so {} ({} bands)", color, temp, scale.symbol(), reason, band.word, scheme.name())
+}
+
+/// Reads a temperature such as "-5", "+3" or "7.5"; `None` for a missing or non-numeric value like "N/A".
+pub fn parse_temp(text: &str) -> Option<f32> {
+    text.trim().parse::<f32>().ok().filter(|temp| temp.is_finite())
+}
+
+/// A temperature rounded to whole degrees for display and banding, so the color always matches the label.
+pub fn parse_temp_rounded(text: &str) -> Option<i32> {
+    parse_temp(text).map(|temp| temp.round() as i32)
+}
+
+pub fn celsius_to_fahrenheit(celsius: i32) -> i32 {
+    (celsius as f32 * 9.0 / 5.0 + 32.0).round() as i32
+}
+
+pub fn kmph_to_mph(kmph: i32) -> i32 {
+    (kmph as f32 * 0.621_371).round() as i32
+}
+
+/// Formats a km/h wind speed from the API in the selected units, or "--" if it isn't a number.
+pub fn format_wind(kmph: &str, units: config::Units) -> String {
+    match kmph.trim().parse::<i32>() {
+        Ok(k) => match units {
+            config::Units::Metric => format!("{} km/h", k),
+            config::Units::Imperial => format!("{} mph", kmph_to_mph(k)),
+            config::Units::Both => format!("{} km/h / {} mph", k, kmph_to_mph(k)),
+        },
+        Err(_) => "--".to_string(),
+    }
+}
+
+/// Formats a Celsius reading from the API in the selected units, or "--" if it isn't a number.
+pub fn format_temp(celsius: &str, units: config::Units) -> String {
+    match parse_temp_rounded(celsius) {
+        Some(c) => match units {
+            config::Units::Metric => format!("{}°C", c),
+            config::Units::Imperial => format!("{}°F", celsius_to_fahrenheit(c)),
+            config::Units::Both => format!("{}°C / {}°F", c, celsius_to_fahrenheit(c)),
+        },
+        None => "--".to_string(),
+    }
+}
+
+/// Colors a dewpoint by comfort: dry below 13°C, sticky up to 18°C, oppressive above.
+pub fn dewpoint_color(dewpoint: i32) -> Color {
+    match dewpoint {
+        d if d < 13 => config::CEEFAX_GREEN,
+        d if d <= 18 => config::CEEFAX_YELLOW,
+        _ => config::CEEFAX_RED,
+    }
+}
+
+/// An arrow for how pressure moved between two readings in millibars; changes under 1 mb count as steady.
+pub fn pressure_trend(previous: i32, current: i32) -> char {
+    match current - previous {
+        d if d >= 1 => '↑',
+        d if d <= -1 => '↓',
+        _ => '→',
+    }
+}
+
+/// The WHO risk band for a UV index, and its color. Readings below zero count as "Low".
+pub fn uv_band(index: i32) -> (&'static str, Color) {
+    match index {
+        i if i >= 11 => ("Extreme", config::CEEFAX_MAGENTA),
+        i if i >= 8 => ("Very High", config::CEEFAX_RED),
+        i if i >= 6 => ("High", config::SUNSET_ORANGE),
+        i if i >= 3 => ("Moderate", config::CEEFAX_YELLOW),
+        _ => ("Low", config::CEEFAX_GREEN),
+    }
+}
+
+/// Returns the hourly slot covering `hour` (0-23), i.e. the latest one starting at or before it.
+pub fn hourly_for_hour(day: &WeatherDay, hour: u32) -> Option<&Hourly> {
+    day.hourly
+        .iter()
+        .rev()
+        .find(|h| h.minutes().is_some_and(|m| m / 60 <= hour))
+        .or_else(|| day.hourly.first())
+}
+
+/// Minutes past midnight for a typed time such as "15", "930", "1530" or "15:30".
+pub fn parse_clock_time(input: &str) -> Option<u32> {
+    if !input.bytes().all(|b| b.is_ascii_digit() || b == b':') {
+        return None;
+    }
+    let (hours, minutes) = match input.split_once(':') {
+        Some(parts) => parts,
+        None if input.len() <= 2 => (input, "0"),
+        None => input.split_at(input.len() - 2),
+    };
+    let (hours, minutes): (u32, u32) = (hours.parse().ok()?, minutes.parse().ok()?);
+    (hours < 24 && minutes < 60).then_some(hours * 60 + minutes)
+}
+
+/// Index of the hourly slot starting closest to `minutes` past midnight.
+pub fn nearest_hourly_index(day: &WeatherDay, minutes: u32) -> Option<usize> {
+    day.hourly
+        .iter()
+        .enumerate()
+        .filter_map(|(i, h)| h.minutes().map(|start| (i, start.abs_diff(minutes))))
+        .min_by_key(|&(_, distance)| distance)
+        .map(|(i, _)| i)
+}
+
+/// Temperature swing across a day, in °C, at which it counts as changeable.
+const VARIABLE_RANGE: i32 = 8;
+
+/// "Variable" when the day's hourly temperatures swing widely, "Steady" otherwise,
+/// or "Unknown" without readings.
+pub fn day_variability(hourly: &[Hourly]) -> &'static str {
+    let temps: Vec<i32> = hourly.iter().filter_map(|h| parse_temp_rounded(&h.tempC)).collect();
+    match (temps.iter().min(), temps.iter().max()) {
+        (Some(min), Some(max)) if max - min >= VARIABLE_RANGE => "Variable",
+        (Some(_), Some(_)) => "Steady",
+        _ => "Unknown",
+    }
+}
+
+/// The next sunrise or sunset after `now`: "Sunset" while the sun is up, otherwise "Sunrise".
+pub fn next_sun_event(report: &WeatherReport, now: NaiveDateTime) -> Option<(&'static str, chrono::Duration)> {
+    let sun_times = |day: &WeatherDay| -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let date = NaiveDate::parse_from_str(&day.date, "%Y-%m-%d").ok()?;
+        let astronomy = day.astronomy.first()?;
+        let sunrise = NaiveTime::parse_from_str(astronomy.sunrise.trim(), "%I:%M %p").ok()?;
+        let sunset = NaiveTime::parse_from_str(astronomy.sunset.trim(), "%I:%M %p").ok()?;
+        Some((date.and_time(sunrise), date.and_time(sunset)))
+    };
+    let (sunrise, sunset) = sun_times(report.weather.first()?)?;
+    if now < sunrise {
+        Some(("Sunrise", sunrise - now))
+    } else if now < sunset {
+        Some(("Sunset", sunset - now))
+    } else {
+        // Tomorrow's sunrise, or today's a day later if the forecast doesn't reach that far.
+        let next_sunrise = report
+            .weather
+            .get(1)
+            .and_then(sun_times)
+            .map_or(sunrise + chrono::Duration::days(1), |(sunrise, _)| sunrise);
+        Some(("Sunrise", next_sunrise - now))
+    }
+}
+
+/// Rewrites an astronomy time such as "09:15 PM" as "21:15", leaving anything else,
+/// like wttr.in's "No sunset" in polar summer, as it came.
+pub fn sun_clock(time: &str) -> String {
+    NaiveTime::parse_from_str(time.trim(), "%I:%M %p").map_or_else(|_| time.trim().to_string(), |time| time.format("%H:%M").to_string())
+}
+
+/// The moon emoji for one of wttr.in's eight named phases, or "" for a phase we don't know.
+pub fn moon_phase_icon(phase: &str) -> &'static str {
+    match phase.trim().to_lowercase().as_str() {
+        "new moon" => "🌑",
+        "waxing crescent" => "🌒",
+        "first quarter" => "🌓",
+        "waxing gibbous" => "🌔",
+        "full moon" => "🌕",
+        "waning gibbous" => "🌖",
+        "last quarter" | "third quarter" => "🌗",
+        "waning crescent" => "🌘",
+        _ => "",
+    }
+}
+
+/// Formats a countdown as "1h 12m", or just "12m" under an hour.
+pub fn format_countdown(remaining: chrono::Duration) -> String {
+    let minutes = remaining.num_minutes().max(0);
+    match minutes / 60 {
+        0 => format!("{}m", minutes),
+        hours => format!("{}h {}m", hours, minutes % 60),
+    }
+}
+
+/// An arrow pointing the way the wind blows, for a 16-point compass direction it blows *from*,
+/// or `None` when the direction is empty or unknown, as for calm air.
+pub fn wind_arrow(dir16: &str) -> Option<char> {
+    const POINTS: [&str; 16] = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW", "NNW",
+    ];
+    // Downwind arrows, starting with a northerly (blowing south) and turning clockwise.
+    const ARROWS: [char; 8] = ['↓', '↙', '←', '↖', '↑', '↗', '→', '↘'];
+    POINTS
+        .iter()
+        .position(|point| point.eq_ignore_ascii_case(dir16.trim()))
+        .map(|index| ARROWS[index.div_ceil(2) % 8])
+}
+
+/// The Beaufort force and its name for a wind speed in km/h, e.g. `(4, "Moderate breeze")` for 20.
+/// Anything from 118 km/h is force 12, and negative speeds count as calm.
+pub fn beaufort(kmph: i32) -> (u8, &'static str) {
+    // The lowest whole km/h of each force from 1 upwards.
+    const LOWER_BOUNDS: [i32; 12] = [1, 6, 12, 20, 29, 39, 50, 62, 75, 89, 103, 118];
+    const NAMES: [&str; 13] = [
+        "Calm", "Light air", "Light breeze", "Gentle breeze", "Moderate breeze", "Fresh breeze", "Strong breeze",
+        "Near gale", "Gale", "Strong gale", "Storm", "Violent storm", "Hurricane force",
+    ];
+    let force = LOWER_BOUNDS.iter().take_while(|&&bound| kmph >= bound).count();
+    (force as u8, NAMES[force])
+}
+
+/// Returns up to `count` hourly slots starting with the one covering `hour` today,
+/// running on into the following days.
+pub fn upcoming_hourly(report: &WeatherReport, hour: u32, count: usize) -> Vec<&Hourly> {
+    let Some(today) = report.weather.first() else {
+        return Vec::new();
+    };
+    let start = today
+        .hourly
+        .iter()
+        .rposition(|h| h.minutes().is_some_and(|m| m / 60 <= hour))
+        .unwrap_or(0);
+    today.hourly[start..]
+        .iter()
+        .chain(report.weather[1..].iter().flat_map(|day| day.hourly.iter()))
+        .take(count)
+        .collect()
+}
+
+/// Maps a weather description string to an icon from the chosen set.
+pub fn get_weather_icon(description: &str, icons: config::IconSet) -> &'static str {
+    let desc_lower = description.to_lowercase();
+    let (emoji, nerdfont, ascii) = match desc_lower {
+        s if s.contains("sunny") => ("☀️", "\u{e30d}", ":)"),
+        s if s.contains("clear") => ("🌙", "\u{e32b}", "()"),
+        s if s.contains("partly cloudy") => ("⛅", "\u{e302}", "-)"),
+        s if s.contains("cloudy") => ("☁️", "\u{e312}", "=="),
+        s if s.contains("overcast") => ("🌥️", "\u{e33d}", "##"),
+        s if s.contains("mist") | s.contains("fog") => ("🌫️", "\u{e313}", "~~"),
+        s if s.contains("drizzle") | s.contains("light rain") => ("🌦️", "\u{e319}", "/."),
+        s if s.contains("rain") | s.contains("shower") => ("🌧️", "\u{e318}", "//"),
+        s if s.contains("sleet") => ("🌨️", "\u{e3ad}", "*/"),
+        s if s.contains("snow") => ("❄️", "\u{e31a}", "**"),
+        s if s.contains("thunder") => ("🌩️", "\u{e31d}", "/!"),
+        _ => ("?", "\u{e374}", "??"),
+    };
+    match icons {
+        config::IconSet::Emoji => emoji,
+        config::IconSet::Nerdfont => nerdfont,
+        config::IconSet::Ascii => ascii,
+    }
+}
+
+/// A plain description standing for a WWO `weatherCode`, worded so the description matchers recognise it.
+fn code_description(code: &str) -> Option<&'static str> {
+    let description = match code.trim().parse::<u16>().ok()? {
+        113 => "Sunny",
+        116 => "Partly cloudy",
+        119 => "Cloudy",
+        122 => "Overcast",
+        143 | 248 | 260 => "Fog",
+        185 | 263 | 266 | 281 | 284 => "Drizzle",
+        176 | 293 | 296 | 311 | 353 => "Light rain",
+        299 | 302 | 305 | 308 | 314 | 356 | 359 => "Rain",
+        182 | 317 | 320 | 350 | 362 | 365 | 374 | 377 => "Sleet",
+        179 | 227 | 230 | 323 | 326 | 329 | 332 | 335 | 338 | 368 | 371 => "Snow",
+        200 | 386 | 389 | 392 | 395 => "Thunder",
+        _ => return None,
+    };
+    Some(description)
+}
+
+/// The description to pick an icon from: the one standing for `code` when it is known, otherwise
+/// `description` itself. wttr.in sends 113 for a clear night too, so "Clear" is kept as written.
+pub fn icon_description<'a>(code: &str, description: &'a str) -> &'a str {
+    match code_description(code) {
+        Some("Sunny") if description.to_lowercase().contains("clear") => description,
+        Some(known) => known,
+        None => description,
+    }
+}
+
+/// A subtle text tint for a condition: warm yellow for sun, cyan for wet weather.
+pub fn get_condition_color(description: &str) -> Color {
     let desc_lower = description.to_lowercase();
     match desc_lower {
-        s if s.contains("sunny") => "☀️",
-        s if s.contains("clear") => "🌙",
-        s if s.contains("partly cloudy") => "⛅",
-        s if s.contains("cloudy") => "☁️",
-        s if s.contains("overcast") => "🌥️",
-        s if s.contains("mist") | s.contains("fog") => "🌫️",
-        s if s.contains("drizzle") | s.contains("light rain") => "🌦️",
-        s if s.contains("rain") | s.contains("shower") => "🌧️",
-        s if s.contains("sleet") => "🌨️",
-        s if s.contains("snow") => "❄️",
-        s if s.contains("thunder") => "🌩️",
-        _ => "?",
+        s if s.contains("sunny") | s.contains("clear") => config::CEEFAX_YELLOW,
+        s if s.contains("rain") | s.contains("drizzle") | s.contains("shower") | s.contains("thunder") => {
+            config::CEEFAX_CYAN
+        }
+        _ => config::CEEFAX_WHITE,
+    }
+}
+
+/// Maps a weather description string to a plain word, for screen readers.
+pub fn get_weather_word(description: &str) -> &'static str {
+    let desc_lower = description.to_lowercase();
+    match desc_lower {
+        s if s.contains("sunny") => "Sunny",
+        s if s.contains("clear") => "Clear",
+        s if s.contains("partly cloudy") => "Partly cloudy",
+        s if s.contains("cloudy") => "Cloudy",
+        s if s.contains("overcast") => "Overcast",
+        s if s.contains("mist") | s.contains("fog") => "Fog",
+        s if s.contains("drizzle") | s.contains("light rain") => "Showers",
+        s if s.contains("rain") | s.contains("shower") => "Rain",
+        s if s.contains("sleet") => "Sleet",
+        s if s.contains("snow") => "Snow",
+        s if s.contains("thunder") => "Thunder",
+        _ => "Unknown",
+    }
+}
+
+/// Picks the icon from the chosen set, or the plain word in text-only mode.
+pub fn condition_marker(description: &str, text_only: bool, icons: config::IconSet) -> &'static str {
+    if text_only {
+        get_weather_word(description)
+    } else {
+        get_weather_icon(description, icons)
     }
 }
 
 
 // --- Unit and Integration Tests ---
+/// A client for tests that answers from canned j1 bodies instead of the network, optionally
+/// failing or taking a while first, and counts the attempts made.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockWeatherClient {
+    /// Cities without a body fail as unknown locations.
+    bodies: HashMap<String, String>,
+    /// Attempts that fail with a network error before any answer.
+    failures: u32,
+    /// How long each attempt is held open.
+    delay: Duration,
+    attempts: std::sync::atomic::AtomicU32,
+    in_flight: std::sync::atomic::AtomicUsize,
+    most_in_flight: std::sync::atomic::AtomicUsize,
+}
+
+#[cfg(test)]
+impl MockWeatherClient {
+    /// Answers each city with its own temperature, with the city's name as the description.
+    pub fn with_temps(temps: &[(&str, i32)]) -> Self {
+        temps
+            .iter()
+            .fold(Self::default(), |mock, &(city, temp)| mock.answer(city, &Self::body(&temp.to_string(), "N", city)))
+    }
+
+    /// A j1 body whose only current condition is `temp`, felt as the same, in a light wind.
+    pub fn body(temp: &str, wind_dir: &str, desc: &str) -> String {
+        format!(
+            r#"{{"current_condition": [{{"temp_C": "{0}", "FeelsLikeC": "{0}", "windspeedKmph": "5", "winddir16Point": "{1}", "precipMM": "0.0", "weatherDesc": [{{"value": "{2}"}}]}}], "weather": []}}"#,
+            temp, wind_dir, desc
+        )
+    }
+
+    /// Answers `city` with `body`, which is decoded like a live response.
+    pub fn answer(mut self, city: &str, body: &str) -> Self {
+        self.bodies.insert(city.to_string(), body.to_string());
+        self
+    }
+
+    /// Fails the first `failures` attempts with a network error.
+    pub fn failing_first(mut self, failures: u32) -> Self {
+        self.failures = failures;
+        self
+    }
+
+    /// Holds each attempt open for `delay`.
+    pub fn taking(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// One attempt at `city`, classified the way the live clients classify theirs.
+    pub fn attempt(&self, city: &str) -> Result<WeatherReport, FetchFailure> {
+        use std::sync::atomic::Ordering;
+        let attempt = self.attempts.fetch_add(1, Ordering::SeqCst) + 1;
+        let now = self.in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+        self.most_in_flight.fetch_max(now, Ordering::SeqCst);
+        std::thread::sleep(self.delay);
+        self.in_flight.fetch_sub(1, Ordering::SeqCst);
+        if attempt <= self.failures {
+            return Err(FetchFailure::Transient("Network request failed: timed out".to_string()));
+        }
+        let body = self
+            .bodies
+            .get(city)
+            .ok_or_else(|| FetchFailure::Permanent(format!("Unknown location '{}'", city)))?;
+        parse_report(body).map_err(FetchFailure::Permanent)
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// The most attempts that were ever in progress at once.
+    pub fn most_in_flight(&self) -> usize {
+        self.most_in_flight.load(std::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+impl WeatherClient for MockWeatherClient {
+    fn fetch(&self, city: &str) -> Result<WeatherReport, String> {
+        self.attempt(city).map_err(|(FetchFailure::Transient(e) | FetchFailure::Permanent(e))| e)
+    }
+
+    fn provider_name(&self) -> &'static str {
+        "mock"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_temperature_colors() {
-        assert_eq!(get_temp_color(5), config::CEEFAX_GREEN);
-        assert_eq!(get_temp_color(14), config::CEEFAX_CYAN);
-        assert_eq!(get_temp_color(25), config::CEEFAX_YELLOW);
+        assert_eq!(get_temp_color(5, TempScale::Celsius, TempScheme::Coarse), config::CEEFAX_GREEN);
+        assert_eq!(get_temp_color(14, TempScale::Celsius, TempScheme::Coarse), config::CEEFAX_CYAN);
+        assert_eq!(get_temp_color(25, TempScale::Celsius, TempScheme::Coarse), config::CEEFAX_YELLOW);
+    }
+
+    #[test]
+    fn test_temp_bands_match_colors() {
+        assert_eq!(get_temp_color(9, TempScale::Celsius, TempScheme::Coarse), TEMP_BANDS[0].color);
+        assert_eq!(get_temp_color(10, TempScale::Celsius, TempScheme::Coarse), TEMP_BANDS[1].color);
+        assert_eq!(get_temp_color(15, TempScale::Celsius, TempScheme::Coarse), TEMP_BANDS[2].color);
+        assert_eq!(get_temp_band(-20, TempScale::Celsius, TempScheme::Coarse).word, "COLD");
+        assert_eq!(get_temp_band(40, TempScale::Celsius, TempScheme::Coarse).word, "WARM");
+    }
+
+    /// An hourly slot at `time` with temperature `temp`, everything else empty.
+    fn hourly(time: &str, temp: &str) -> Hourly {
+        Hourly { time: time.to_string(), tempC: temp.to_string(), ..Default::default() }
+    }
+
+    fn day(date: &str, hourly: Vec<Hourly>) -> WeatherDay {
+        WeatherDay { date: date.to_string(), hourly, ..Default::default() }
+    }
+
+    #[test]
+    fn test_day_variability() {
+        let hours = |temps: &[&str]| -> Vec<Hourly> { temps.iter().map(|temp| hourly("0", temp)).collect() };
+        assert_eq!(day_variability(&hours(&["12", "13", "14", "13", "12"])), "Steady");
+        assert_eq!(day_variability(&hours(&["4", "9", "17", "11"])), "Variable");
+        assert_eq!(day_variability(&hours(&["n/a"])), "Unknown");
+        assert_eq!(day_variability(&[]), "Unknown");
+    }
+
+    #[test]
+    fn test_slot_times_in_either_format_and_granularity() {
+        assert_eq!(slot_minutes("0"), Some(0));
+        assert_eq!(slot_minutes("300"), Some(180));
+        assert_eq!(slot_minutes("1530"), Some(930));
+        assert_eq!(slot_minutes("0:00"), Some(0));
+        assert_eq!(slot_minutes("15:00"), Some(900));
+        assert_eq!(slot_minutes("2400"), None);
+        assert_eq!(slot_minutes("975"), None);
+        assert_eq!(slot_minutes("noon"), None);
+        assert_eq!(format_slot_time(930), "15:30");
+
+        let slots = |times: Vec<String>| day("2024-06-01", times.iter().map(|time| hourly(time, "10")).collect());
+        let three_hourly = slots((0..8).map(|i| (i * 300).to_string()).collect());
+        let hourly = slots((0..24).map(|h| format!("{}:00", h)).collect());
+
+        assert_eq!(hourly_for_hour(&three_hourly, 14).unwrap().time, "1200");
+        assert_eq!(hourly_for_hour(&hourly, 14).unwrap().time, "14:00");
+        assert_eq!(nearest_hourly_index(&three_hourly, 16 * 60 + 40), Some(6));
+        assert_eq!(nearest_hourly_index(&hourly, 16 * 60 + 40), Some(17));
+    }
+
+    #[test]
+    fn test_go_to_time_finds_nearest_slot() {
+        assert_eq!(parse_clock_time("15"), Some(900));
+        assert_eq!(parse_clock_time("930"), Some(570));
+        assert_eq!(parse_clock_time("15:30"), Some(930));
+        assert_eq!(parse_clock_time("27"), None);
+        assert_eq!(parse_clock_time("1275"), None);
+        assert_eq!(parse_clock_time(""), None);
+
+        let day = day("2024-06-01", ["0", "300", "600", "900", "1200", "1500", "1800", "2100"].iter().map(|time| hourly(time, "10")).collect());
+        assert_eq!(nearest_hourly_index(&day, 15 * 60), Some(5));
+        assert_eq!(nearest_hourly_index(&day, 16 * 60 + 40), Some(6));
+        assert_eq!(nearest_hourly_index(&day, 23 * 60 + 59), Some(7));
+    }
+
+    #[test]
+    fn test_next_sun_event() {
+        let sun_day = |date: &str, sunrise: &str, sunset: &str| WeatherDay {
+            astronomy: vec![Astronomy { sunrise: sunrise.to_string(), sunset: sunset.to_string(), ..Default::default() }],
+            ..day(date, Vec::new())
+        };
+        let report = WeatherReport {
+            current_condition: Vec::new(),
+            weather: vec![sun_day("2024-06-01", "04:45 AM", "09:15 PM"), sun_day("2024-06-02", "04:44 AM", "09:16 PM")],
+        };
+        let at = |time: &str| NaiveDateTime::parse_from_str(&format!("2024-06-01 {}", time), "%Y-%m-%d %H:%M").unwrap();
+
+        let (event, remaining) = next_sun_event(&report, at("20:03")).unwrap();
+        assert_eq!((event, format_countdown(remaining).as_str()), ("Sunset", "1h 12m"));
+        let (event, remaining) = next_sun_event(&report, at("21:30")).unwrap();
+        assert_eq!((event, format_countdown(remaining).as_str()), ("Sunrise", "7h 14m"));
+        let (event, remaining) = next_sun_event(&report, at("04:30")).unwrap();
+        assert_eq!((event, format_countdown(remaining).as_str()), ("Sunrise", "15m"));
+
+        let no_astronomy = WeatherReport { current_condition: Vec::new(), weather: vec![day("2024-06-01", Vec::new())] };
+        assert!(next_sun_event(&no_astronomy, at("12:00")).is_none());
+    }
+
+    #[test]
+    fn test_astronomy_is_parsed_and_optional() {
+        let body = r#"{"current_condition": [], "weather": [
+            {"date": "2024-06-01", "astronomy": [{"moon_illumination": "48", "moon_phase": "First Quarter", "sunrise": "04:45 AM", "sunset": "09:15 PM"}], "hourly": []},
+            {"date": "2024-06-02", "astronomy": [], "hourly": []},
+            {"date": "2024-06-03", "hourly": []}
+        ]}"#;
+        let report = parse_report(body).unwrap();
+
+        let today = report.weather[0].astronomy.first().unwrap();
+        assert_eq!((sun_clock(&today.sunrise), sun_clock(&today.sunset)), ("04:45".to_string(), "21:15".to_string()));
+        assert_eq!((today.moon_phase.as_str(), today.moon_illumination.as_str()), ("First Quarter", "48"));
+        assert!(report.weather[1].astronomy.is_empty());
+        assert!(report.weather[2].astronomy.is_empty());
+        assert_eq!(sun_clock("No sunset"), "No sunset");
+    }
+
+    #[test]
+    fn test_moon_phase_icons() {
+        let phases = [
+            ("New Moon", "🌑"),
+            ("Waxing Crescent", "🌒"),
+            ("First Quarter", "🌓"),
+            ("Waxing Gibbous", "🌔"),
+            ("Full Moon", "🌕"),
+            ("Waning Gibbous", "🌖"),
+            ("Last Quarter", "🌗"),
+            ("Waning Crescent", "🌘"),
+        ];
+        for (phase, icon) in phases {
+            assert_eq!(moon_phase_icon(phase), icon, "{}", phase);
+        }
+        assert_eq!(moon_phase_icon("full moon"), "🌕");
+        assert_eq!(moon_phase_icon("Blue Moon"), "");
+    }
+
+    #[test]
+    fn test_wind_arrows_point_downwind() {
+        let points = [
+            ("N", '↓'), ("NNE", '↙'), ("NE", '↙'), ("ENE", '←'),
+            ("E", '←'), ("ESE", '↖'), ("SE", '↖'), ("SSE", '↑'),
+            ("S", '↑'), ("SSW", '↗'), ("SW", '↗'), ("WSW", '→'),
+            ("W", '→'), ("WNW", '↘'), ("NW", '↘'), ("NNW", '↓'),
+        ];
+        for (point, arrow) in points {
+            assert_eq!(wind_arrow(point), Some(arrow), "{}", point);
+        }
+        assert_eq!(wind_arrow("sw"), Some('↗'));
+        assert_eq!(wind_arrow("calm"), None);
+        assert_eq!(wind_arrow(""), None);
+    }
+
+    #[test]
+    fn test_explain_temp_color() {
+        assert_eq!(
+            explain_temp_color(18, TempScale::Celsius, TempScheme::Coarse),
+            "YELLOW: 18°C is at or above the 15° threshold, so WARM (COARSE bands)"
+        );
+        assert_eq!(
+            explain_temp_color(12, TempScale::Celsius, TempScheme::Fine),
+            "CYAN: 12°C is between the 10° and 15° thresholds, so MILD (FINE bands)"
+        );
+        assert_eq!(
+            explain_temp_color(-3, TempScale::Celsius, TempScheme::Fine),
+            "WHITE: -3°C is below the 0° threshold, so FREEZING (FINE bands)"
+        );
     }
 
-    /// A mock client for testing without network access.
-    struct MockWeatherClient {
-        mock_data: String,
+    #[test]
+    fn test_fahrenheit_conversion_and_band_boundaries() {
+        assert_eq!(celsius_to_fahrenheit(0), 32);
+        assert_eq!(celsius_to_fahrenheit(10), 50);
+        assert_eq!(celsius_to_fahrenheit(-40), -40);
+        assert_eq!(kmph_to_mph(20), 12);
+        assert_eq!(format_wind("20", config::Units::Imperial), "12 mph");
+        assert_eq!(format_wind("20", config::Units::Both), "20 km/h / 12 mph");
+
+        let f = TempScale::Fahrenheit;
+        assert_eq!(get_temp_color(49, f, TempScheme::Coarse), config::CEEFAX_GREEN);
+        assert_eq!(get_temp_color(50, f, TempScheme::Coarse), config::CEEFAX_CYAN);
+        assert_eq!(get_temp_color(58, f, TempScheme::Coarse), config::CEEFAX_CYAN);
+        assert_eq!(get_temp_color(59, f, TempScheme::Coarse), config::CEEFAX_YELLOW);
+        assert_eq!(get_temp_color(31, f, TempScheme::Fine), config::CEEFAX_WHITE);
+        assert_eq!(band_label(TempScheme::Coarse, 1, f), "50-59°");
+        assert_eq!(band_label(TempScheme::Coarse, 0, TempScale::Celsius), "Below 10°");
+        assert_eq!(band_label(TempScheme::Coarse, 2, TempScale::Celsius), "Above 15°");
+        assert_eq!(
+            explain_temp_color(64, f, TempScheme::Coarse),
+            "YELLOW: 64°F is at or above the 59° threshold, so WARM (COARSE bands)"
+        );
     }
 
-    impl WeatherClient for MockWeatherClient {
-        fn fetch(&self, _city: &str) -> Result<WeatherReport, String> {
-            serde_json::from_str(&self.mock_data)
-                .map_err(|e| format!("Mock data parsing failed: {}", e))
+    #[test]
+    fn test_temp_schemes_cycle_and_band() {
+        assert_eq!(TempScheme::Coarse.next().next().next(), TempScheme::Coarse);
+        assert_eq!(get_temp_color(-1, TempScale::Celsius, TempScheme::Fine), config::CEEFAX_WHITE);
+        assert_eq!(get_temp_color(20, TempScale::Celsius, TempScheme::Fine), config::CEEFAX_RED);
+        assert_eq!(get_temp_color(14, TempScale::Celsius, TempScheme::Seasonal), config::CEEFAX_GREEN);
+        assert_eq!(get_temp_band(25, TempScale::Celsius, TempScheme::Seasonal).word, "WARM");
+        for scheme in [TempScheme::Coarse, TempScheme::Fine, TempScheme::Seasonal] {
+            assert_eq!(scheme.bands().last().unwrap().upper, i32::MAX);
         }
     }
 
@@ -151,38 +1142,332 @@ mod tests {
                     "windspeedKmph": "10",
                     "winddir16Point": "W",
                     "precipMM": "0.0",
+                    "humidity": "62",
+                    "uvIndex": "4",
+                    "pressure": "1016",
                     "weatherDesc": [{"value": "Sunny"}]
                 }
             ],
             "weather": [
                 {
+                    "date": "2024-06-01",
                     "hourly": [
-                        {"time": "0", "tempC": "10", "weatherDesc": [{"value": "Clear"}]},
-                        {"time": "300", "tempC": "12", "weatherDesc": [{"value": "Partly cloudy"}]}
+                        {"time": "0", "tempC": "10", "DewPointC": "6", "weatherDesc": [{"value": "Clear"}]},
+                        {"time": "300", "tempC": "12", "DewPointC": "7", "weatherDesc": [{"value": "Partly cloudy"}]}
                     ]
                 }
             ]
         }
         "#;
 
-        let mock_client = MockWeatherClient {
-            mock_data: mock_json.to_string(),
-        };
+        let mock_client = MockWeatherClient::default().answer("test-city", mock_json);
 
         let result = mock_client.fetch("test-city");
         assert!(result.is_ok());
         let report = result.unwrap();
         assert_eq!(report.current_condition[0].temp_C, "15");
+        assert_eq!(report.current_condition[0].humidity, "62");
+        assert_eq!(report.current_condition[0].uvIndex, "4");
+        assert_eq!(report.current_condition[0].pressure, "1016");
         assert_eq!(report.weather[0].hourly.len(), 2);
+        assert_eq!(report.weather[0].hourly[1].DewPointC, "7");
+        assert_eq!(hourly_for_hour(&report.weather[0], 2).unwrap().time, "0");
+        assert_eq!(hourly_for_hour(&report.weather[0], 14).unwrap().time, "300");
+
+        let upcoming: Vec<&str> = upcoming_hourly(&report, 1, 5).iter().map(|h| h.time.as_str()).collect();
+        assert_eq!(upcoming, vec!["0", "300"]);
+        assert_eq!(upcoming_hourly(&report, 4, 1)[0].time, "300");
+        let empty = WeatherReport { current_condition: Vec::new(), weather: Vec::new() };
+        assert!(upcoming_hourly(&empty, 4, 5).is_empty());
+    }
+
+    #[test]
+    fn test_live_fetch_uses_fresh_cache_and_refetches_stale() {
+        let dir = std::env::temp_dir().join(format!("ceefax-weather-test-live-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let body = MockWeatherClient::body("9", "SW", "Cloudy");
+        DiskCache::new(dir.clone(), Duration::from_secs(60)).store("Leeds", &body).unwrap();
+        // Nothing listens here, so any request that reaches the network fails at once.
+        let options = config::FetchOptions {
+            proxy: Some("http://127.0.0.1:9".to_string()),
+            ..Default::default()
+        };
+
+        let fresh = LiveWeatherClient::new(Some(DiskCache::new(dir.clone(), Duration::from_secs(60))), &options).unwrap();
+        assert_eq!(fresh.fetch("Leeds").unwrap().current_condition[0].temp_C, "9");
+
+        let stale = LiveWeatherClient::new(Some(DiskCache::new(dir.clone(), Duration::ZERO)), &options).unwrap();
+        assert!(stale.fetch("Leeds").unwrap_err().contains("proxy"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_forced_fetch_skips_fresh_cache() {
+        let dir = std::env::temp_dir().join(format!("ceefax-weather-test-forced-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let body = MockWeatherClient::body("9", "SW", "Cloudy");
+        DiskCache::new(dir.clone(), Duration::from_secs(60)).store("Leeds", &body).unwrap();
+        let options = config::FetchOptions {
+            proxy: Some("http://127.0.0.1:9".to_string()),
+            ..Default::default()
+        };
+        let client: Arc<dyn WeatherClient> =
+            Arc::new(LiveWeatherClient::new(Some(DiskCache::new(dir.clone(), Duration::from_secs(60))), &options).unwrap());
+
+        assert!(client.fetch("Leeds").is_ok());
+        assert!(client.fetch_fresh("Leeds").unwrap_err().contains("proxy"));
+        assert!(Uncached(client).fetch("Leeds").unwrap_err().contains("proxy"));
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn test_retries_back_off_then_succeed_but_skip_decode_errors() {
+        let body = r#"{"current_condition": [], "weather": []}"#;
+        let flaky = MockWeatherClient::default().answer("Leeds", body).failing_first(2);
+        let mut delays = Vec::new();
+        assert!(retry_with_backoff(3, |d| delays.push(d), || flaky.attempt("Leeds")).is_ok());
+        assert_eq!(flaky.attempts(), 3);
+        assert_eq!(delays, vec![Duration::from_millis(200), Duration::from_millis(400)]);
+
+        let down = MockWeatherClient::default().answer("Leeds", body).failing_first(10);
+        let mut delays = Vec::new();
+        assert!(retry_with_backoff(3, |d| delays.push(d), || down.attempt("Leeds")).unwrap_err().contains("timed out"));
+        assert_eq!(down.attempts(), 4);
+        assert_eq!(delays.last(), Some(&Duration::from_millis(800)));
+
+        let garbled = MockWeatherClient::default().answer("Leeds", "{");
+        assert!(retry_with_backoff(3, |_| panic!("decode errors must not be retried"), || garbled.attempt("Leeds")).is_err());
+        assert_eq!(garbled.attempts(), 1);
+    }
+
+    #[test]
+    fn test_client_errors_are_not_retried() {
+        use reqwest::StatusCode;
+
+        assert!(matches!(status_failure(StatusCode::NOT_FOUND), FetchFailure::Permanent(e) if e.contains("404")));
+        assert!(matches!(status_failure(StatusCode::TOO_MANY_REQUESTS), FetchFailure::Permanent(_)));
+        assert!(matches!(status_failure(StatusCode::SERVICE_UNAVAILABLE), FetchFailure::Transient(e) if e.contains("503")));
+    }
+
+    #[test]
+    fn test_fallback_queries_are_tried_in_order() {
+        let client = MockWeatherClient::default()
+            .answer("Newport", r#"{"current_condition": [], "weather": []}"#)
+            .answer("NP20", &MockWeatherClient::body("9", "SW", "Light rain"));
+
+        let found = fetch_first_valid(&client, &["Nowhere", "NP20", "Newport"]).unwrap().unwrap();
+        assert_eq!(found.current_condition[0].temp_C, "9");
+        assert!(fetch_first_valid(&client, &["Nowhere", "Newport"]).unwrap().is_none());
+        assert_eq!(fetch_first_valid(&client, &["Newport", "Nowhere"]).unwrap_err(), "Unknown location 'Nowhere'");
+    }
+
+    #[test]
+    fn test_parse_temp_handles_signs_decimals_and_gaps() {
+        assert_eq!(parse_temp("-5"), Some(-5.0));
+        assert_eq!(parse_temp("7.5"), Some(7.5));
+        assert_eq!(parse_temp(" +3 "), Some(3.0));
+        assert_eq!(parse_temp(""), None);
+        assert_eq!(parse_temp("N/A"), None);
+        assert_eq!(parse_temp("NaN"), None);
+        assert_eq!(parse_temp_rounded("7.5"), Some(8));
+        assert_eq!(parse_temp_rounded("-2.6"), Some(-3));
+        assert_eq!(format_temp("9.6", config::Units::Metric), "10°C");
+    }
+
+    #[test]
+    fn test_temperature_formatting() {
+        assert_eq!(celsius_to_fahrenheit(0), 32);
+        assert_eq!(celsius_to_fahrenheit(-40), -40);
+        assert_eq!(celsius_to_fahrenheit(15), 59);
+        assert_eq!(format_temp("15", config::Units::Metric), "15°C");
+        assert_eq!(format_temp("15", config::Units::Imperial), "59°F");
+        assert_eq!(format_temp("15", config::Units::Both), "15°C / 59°F");
+        assert_eq!(format_temp("", config::Units::Both), "--");
+    }
+
+    #[test]
+    fn test_dewpoint_colors() {
+        assert_eq!(dewpoint_color(12), config::CEEFAX_GREEN);
+        assert_eq!(dewpoint_color(13), config::CEEFAX_YELLOW);
+        assert_eq!(dewpoint_color(18), config::CEEFAX_YELLOW);
+        assert_eq!(dewpoint_color(19), config::CEEFAX_RED);
+    }
+
+    #[test]
+    fn test_html_body_gives_friendly_error() {
+        let html = "\n<!DOCTYPE html><html><body>Sorry, we are running out of queries</body></html>";
+        assert_eq!(parse_report(html).unwrap_err(), "wttr.in is busy, try again shortly.");
+        assert!(parse_report("{}").unwrap_err().starts_with("Failed to decode API response"));
+    }
+
+    #[test]
+    fn test_location_url_encoding() {
+        assert_eq!(location_url("London"), "https://wttr.in/London");
+        assert_eq!(location_url("New York"), "https://wttr.in/New%20York");
+        assert_eq!(location_url("München"), "https://wttr.in/M%C3%BCnchen");
+    }
+
+    #[test]
+    fn test_slow_response_times_out_with_clear_message() {
+        // Accepts the connection but never answers.
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let _held: Vec<_> = listener.incoming().take(1).collect();
+            std::thread::sleep(Duration::from_secs(5));
+        });
+        let options = config::FetchOptions {
+            proxy: Some(format!("http://127.0.0.1:{}", port)),
+            timeout: Duration::from_millis(200),
+            ..Default::default()
+        };
+
+        let client = LiveWeatherClient::new(None, &options).unwrap();
+        assert_eq!(client.fetch("Leeds").unwrap_err(), "Request timed out after 0.2s");
+    }
+
+    #[test]
+    fn test_proxy_url_is_validated() {
+        let with_proxy = |url: &str| config::FetchOptions { proxy: Some(url.to_string()), ..Default::default() };
+        assert!(LiveWeatherClient::new(None, &with_proxy("http://proxy.example:3128")).is_ok());
+        let err = LiveWeatherClient::new(None, &with_proxy("not a url")).err().unwrap();
+        assert!(err.starts_with("Invalid proxy URL 'not a url'"));
+    }
+
+    #[test]
+    fn test_proxy_flag_takes_precedence_over_the_env() {
+        // The environment's proxy only accepts connections, so a request sent to it times out
+        // and leaves a connection behind to be seen here.
+        let env_proxy = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        env_proxy.set_nonblocking(true).unwrap();
+        let env_url = format!("http://{}", env_proxy.local_addr().unwrap());
+        let proxy_vars = ["HTTP_PROXY", "HTTPS_PROXY", "http_proxy", "https_proxy"];
+        for var in proxy_vars {
+            std::env::set_var(var, &env_url);
+        }
+        let options = |proxy: Option<&str>| config::FetchOptions {
+            proxy: proxy.map(str::to_string),
+            retries: 0,
+            timeout: Duration::from_millis(300),
+        };
+        let through_env = LiveWeatherClient::new(None, &options(None)).unwrap().fetch_fresh("Leeds");
+        // Nothing listens on port 9, so the flag's proxy refuses at once.
+        let through_flag = LiveWeatherClient::new(None, &options(Some("http://127.0.0.1:9"))).unwrap().fetch_fresh("Leeds");
+        for var in proxy_vars {
+            std::env::remove_var(var);
+        }
+
+        assert!(through_env.is_err());
+        assert!(env_proxy.accept().is_ok(), "the environment's proxy was not used without --proxy");
+        assert!(through_flag.unwrap_err().contains("Could not connect through proxy http://127.0.0.1:9"));
+        assert!(env_proxy.accept().is_err(), "--proxy went through the environment's proxy");
+    }
+
+    #[test]
+    fn test_beaufort_band_boundaries() {
+        let boundaries = [(1, 1), (6, 2), (12, 3), (20, 4), (29, 5), (39, 6), (50, 7), (62, 8), (75, 9), (89, 10), (103, 11), (118, 12)];
+        for (kmph, force) in boundaries {
+            assert_eq!(beaufort(kmph).0, force, "{} km/h", kmph);
+            assert_eq!(beaufort(kmph - 1).0, force - 1, "{} km/h", kmph - 1);
+        }
+        assert_eq!(beaufort(0), (0, "Calm"));
+        assert_eq!(beaufort(-3), (0, "Calm"));
+        assert_eq!(beaufort(20), (4, "Moderate breeze"));
+        assert_eq!(beaufort(70), (8, "Gale"));
+        assert_eq!(beaufort(250), (12, "Hurricane force"));
+    }
+
+    #[test]
+    fn test_pressure_trend() {
+        assert_eq!(pressure_trend(1012, 1015), '↑');
+        assert_eq!(pressure_trend(1012, 1011), '↓');
+        assert_eq!(pressure_trend(1012, 1012), '→');
+    }
+
+    #[test]
+    fn test_uv_bands_at_who_boundaries() {
+        let word = |index| uv_band(index).0;
+        assert_eq!((word(0), word(2), word(3)), ("Low", "Low", "Moderate"));
+        assert_eq!((word(5), word(6)), ("Moderate", "High"));
+        assert_eq!((word(7), word(8)), ("High", "Very High"));
+        assert_eq!((word(10), word(11)), ("Very High", "Extreme"));
+        assert_eq!(word(-4), "Low");
+        assert_eq!(uv_band(6).1, config::SUNSET_ORANGE);
     }
 
     #[test]
     fn test_weather_icons() {
-        assert_eq!(get_weather_icon("Sunny"), "☀️");
-        assert_eq!(get_weather_icon("Light rain shower"), "🌦️");
-        assert_eq!(get_weather_icon("Heavy snow"), "❄️");
-        assert_eq!(get_weather_icon("Thundery outbreaks possible"), "🌩️");
-        assert_eq!(get_weather_icon("Unknown description"), "?");
+        use config::IconSet;
+        assert_eq!(get_weather_icon("Sunny", IconSet::Emoji), "☀️");
+        assert_eq!(get_weather_icon("Light rain shower", IconSet::Emoji), "🌦️");
+        assert_eq!(get_weather_icon("Heavy snow", IconSet::Emoji), "❄️");
+        assert_eq!(get_weather_icon("Thundery outbreaks possible", IconSet::Emoji), "🌩️");
+        assert_eq!(get_weather_icon("Unknown description", IconSet::Emoji), "?");
+
+        // Escapes, so a re-encoded source file can't mangle both sides of the comparison alike.
+        let expected = [
+            ("Sunny", "\u{2600}\u{fe0f}"),
+            ("Clear", "\u{1f319}"),
+            ("Partly cloudy", "\u{26c5}"),
+            ("Cloudy", "\u{2601}\u{fe0f}"),
+            ("Overcast", "\u{1f325}\u{fe0f}"),
+            ("Mist", "\u{1f32b}\u{fe0f}"),
+            ("Patchy light drizzle", "\u{1f326}\u{fe0f}"),
+            ("Moderate rain", "\u{1f327}\u{fe0f}"),
+            ("Light sleet", "\u{1f328}\u{fe0f}"),
+            ("Heavy snow", "\u{2744}\u{fe0f}"),
+            ("Thundery outbreaks possible", "\u{1f329}\u{fe0f}"),
+        ];
+        for (description, icon) in expected {
+            assert_eq!(get_weather_icon(description, IconSet::Emoji), icon, "{}", description);
+        }
+
+        assert_eq!(get_weather_icon("Sunny", IconSet::Nerdfont), "\u{e30d}");
+        assert_eq!(get_weather_icon("Partly cloudy", IconSet::Nerdfont), "\u{e302}");
+        assert_eq!(get_weather_icon("Moderate rain", IconSet::Nerdfont), "\u{e318}");
+        assert_eq!(get_weather_icon("Heavy snow", IconSet::Nerdfont), "\u{e31a}");
+        assert_eq!(get_weather_icon("Unknown description", IconSet::Nerdfont), "\u{e374}");
+
+        assert_eq!(get_weather_icon("Sunny", IconSet::Ascii), ":)");
+        assert_eq!(get_weather_icon("Cloudy", IconSet::Ascii), "==");
+        assert_eq!(get_weather_icon("Moderate rain", IconSet::Ascii), "//");
+        assert_eq!(get_weather_icon("Heavy snow", IconSet::Ascii), "**");
+        assert_eq!(get_weather_icon("Unknown description", IconSet::Ascii), "??");
+    }
+
+    #[test]
+    fn test_weather_icons_by_code() {
+        let by_code = |code| get_weather_icon(icon_description(code, "Unknown"), config::IconSet::Emoji);
+        assert_eq!(by_code("113"), "☀️");
+        assert_eq!(by_code("116"), "⛅");
+        assert_eq!(by_code("296"), "🌦️");
+        assert_eq!(by_code("338"), "❄️");
+        assert_eq!(by_code("389"), "🌩️");
+        assert_eq!(by_code("999"), get_weather_icon("Unknown", config::IconSet::Emoji));
+
+        // The code wins over an unusual phrasing, and the description is used when the code is unknown.
+        assert_eq!(icon_description("263", "Patchy light drizzle"), "Drizzle");
+        assert_eq!(icon_description("", "Patchy light drizzle"), "Patchy light drizzle");
+        assert_eq!(icon_description("113", "Clear"), "Clear");
+        assert_eq!(get_weather_icon(icon_description("266", "Bruine légère"), config::IconSet::Emoji), "🌦️");
+    }
+
+    #[test]
+    fn test_condition_colors() {
+        assert_eq!(get_condition_color("Sunny"), config::CEEFAX_YELLOW);
+        assert_eq!(get_condition_color("Patchy light drizzle"), config::CEEFAX_CYAN);
+        assert_eq!(get_condition_color("Overcast"), config::CEEFAX_WHITE);
+    }
+
+    #[test]
+    fn test_text_only_markers() {
+        use config::IconSet;
+        assert_eq!(condition_marker("Sunny", true, IconSet::Emoji), "Sunny");
+        assert_eq!(condition_marker("Light rain shower", true, IconSet::Emoji), "Showers");
+        assert_eq!(condition_marker("Heavy snow", true, IconSet::Ascii), "Snow");
+        assert_eq!(condition_marker("Unknown description", true, IconSet::Emoji), "Unknown");
+        assert_eq!(condition_marker("Sunny", false, IconSet::Emoji), "☀️");
+        assert_eq!(condition_marker("Sunny", false, IconSet::Ascii), ":)");
     }
 }
 